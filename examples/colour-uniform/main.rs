@@ -147,6 +147,7 @@ impl<B: Backend> RendererState<B> {
                     count: 1,
                     stage_flags: pso::ShaderStageFlags::FRAGMENT,
                     immutable_samplers: false,
+                    partially_bound: false,
                 },
                 pso::DescriptorSetLayoutBinding {
                     binding: 1,
@@ -154,6 +155,7 @@ impl<B: Backend> RendererState<B> {
                     count: 1,
                     stage_flags: pso::ShaderStageFlags::FRAGMENT,
                     immutable_samplers: false,
+                    partially_bound: false,
                 },
             ],
         );
@@ -171,6 +173,7 @@ impl<B: Backend> RendererState<B> {
                 count: 1,
                 stage_flags: pso::ShaderStageFlags::FRAGMENT,
                 immutable_samplers: false,
+                partially_bound: false,
             }],
         );
 
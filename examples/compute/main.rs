@@ -94,6 +94,7 @@ fn main() {
                     count: 1,
                     stage_flags: pso::ShaderStageFlags::COMPUTE,
                     immutable_samplers: false,
+                    partially_bound: false,
                 }),
                 iter::empty(),
             )
@@ -234,6 +234,7 @@ where
                         count: 1,
                         stage_flags: ShaderStageFlags::MESH,
                         immutable_samplers: false,
+                        partially_bound: false,
                     }),
                     iter::empty(),
                 )
@@ -29,7 +29,7 @@ pub fn wasm_main() {
 
 use hal::{
     buffer, command, display, format as f,
-    format::{AsFormat, ChannelType, Rgba8Srgb as ColorFormat, Swizzle},
+    format::{AsFormat, Rgba8Srgb as ColorFormat, Swizzle},
     image as i, memory as m, pass,
     pass::Subpass,
     pool,
@@ -356,6 +356,7 @@ where
                             count: 1,
                             stage_flags: ShaderStageFlags::FRAGMENT,
                             immutable_samplers: false,
+                            partially_bound: false,
                         },
                         pso::DescriptorSetLayoutBinding {
                             binding: 1,
@@ -363,6 +364,7 @@ where
                             count: 1,
                             stage_flags: ShaderStageFlags::FRAGMENT,
                             immutable_samplers: false,
+                            partially_bound: false,
                         },
                     ]
                     .into_iter(),
@@ -693,13 +695,7 @@ where
         let caps = surface.capabilities(&adapter.physical_device);
         let formats = surface.supported_formats(&adapter.physical_device);
         println!("formats: {:?}", formats);
-        let format = formats.map_or(f::Format::Rgba8Srgb, |formats| {
-            formats
-                .iter()
-                .find(|format| format.base_format().1 == ChannelType::Srgb)
-                .map(|format| *format)
-                .unwrap_or(formats[0])
-        });
+        let format = window::select_format(formats.as_deref(), f::Format::Rgba8Srgb);
 
         let swap_config = window::SwapchainConfig::from_caps(&caps, format, DIMS);
         let fat = swap_config.framebuffer_attachment();
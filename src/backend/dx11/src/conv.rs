@@ -4,8 +4,8 @@ use hal::{
     image,
     pso::{
         BlendDesc, BlendOp, ColorBlendDesc, Comparison, DepthBias, DepthStencilDesc, Face, Factor,
-        FrontFace, InputAssemblerDesc, Multisampling, PolygonMode, Rasterizer, Rect, Sided, State,
-        StencilFace, StencilOp, StencilValue, Viewport,
+        FrontFace, InputAssemblerDesc, LogicOp, Multisampling, PolygonMode, Rasterizer, Rect,
+        Sided, State, StencilFace, StencilOp, StencilValue, Viewport,
     },
     IndexType,
 };
@@ -17,7 +17,7 @@ use winapi::{
         dxgiformat::*,
         minwindef::{FALSE, INT, TRUE},
     },
-    um::{d3d11::*, d3dcommon::*},
+    um::{d3d11::*, d3d11_1::*, d3dcommon::*},
 };
 
 use std::mem;
@@ -29,10 +29,18 @@ pub fn map_index_type(ty: IndexType) -> DXGI_FORMAT {
     }
 }
 
-// TODO: add aspect parameter
-pub fn viewable_format(format: DXGI_FORMAT) -> DXGI_FORMAT {
+pub fn viewable_format(format: DXGI_FORMAT, aspects: Aspects) -> DXGI_FORMAT {
+    if aspects == Aspects::STENCIL {
+        match format {
+            DXGI_FORMAT_D32_FLOAT_S8X24_UINT => return DXGI_FORMAT_X32_TYPELESS_G8X24_UINT,
+            DXGI_FORMAT_D24_UNORM_S8_UINT => return DXGI_FORMAT_X24_TYPELESS_G8_UINT,
+            _ => {}
+        }
+    }
+
     match format {
         DXGI_FORMAT_D32_FLOAT_S8X24_UINT => DXGI_FORMAT_R32_FLOAT_X8X24_TYPELESS,
+        DXGI_FORMAT_D24_UNORM_S8_UINT => DXGI_FORMAT_R24_UNORM_X8_TYPELESS,
         DXGI_FORMAT_D32_FLOAT => DXGI_FORMAT_R32_FLOAT,
         DXGI_FORMAT_D16_UNORM => DXGI_FORMAT_R16_UNORM,
         _ => format,
@@ -94,6 +102,7 @@ pub fn map_format(format: Format) -> Option<DXGI_FORMAT> {
         E5b9g9r9Ufloat => DXGI_FORMAT_R9G9B9E5_SHAREDEXP,
         D16Unorm => DXGI_FORMAT_D16_UNORM,
         D32Sfloat => DXGI_FORMAT_D32_FLOAT,
+        D24UnormS8Uint => DXGI_FORMAT_D24_UNORM_S8_UINT,
         D32SfloatS8Uint => DXGI_FORMAT_D32_FLOAT_S8X24_UINT,
         Bc1RgbUnorm | Bc1RgbaUnorm => DXGI_FORMAT_BC1_UNORM,
         Bc1RgbSrgb | Bc1RgbaSrgb => DXGI_FORMAT_BC1_UNORM_SRGB,
@@ -278,6 +287,17 @@ impl DecomposedDxgiFormat {
                 copy_srv: Some(DXGI_FORMAT_R32_FLOAT_X8X24_TYPELESS),
             },
 
+            DXGI_FORMAT_D24_UNORM_S8_UINT => DecomposedDxgiFormat {
+                typeless: DXGI_FORMAT_R24G8_TYPELESS,
+                // TODO: depth or stencil?
+                srv: Some(DXGI_FORMAT_R24_UNORM_X8_TYPELESS),
+                rtv: None,
+                uav: None,
+                dsv: Some(format),
+                copy_uav: None,
+                copy_srv: Some(DXGI_FORMAT_R24_UNORM_X8_TYPELESS),
+            },
+
             DXGI_FORMAT_D32_FLOAT => DecomposedDxgiFormat {
                 typeless: DXGI_FORMAT_R32_TYPELESS,
                 srv: Some(DXGI_FORMAT_R32_FLOAT),
@@ -629,7 +649,7 @@ fn map_blend_targets(
 ) -> [D3D11_RENDER_TARGET_BLEND_DESC; 8] {
     let mut targets: [D3D11_RENDER_TARGET_BLEND_DESC; 8] = [unsafe { mem::zeroed() }; 8];
 
-    for (mut target, color_desc) in targets.iter_mut().zip(render_target_blends.iter()) {
+    for (target, color_desc) in targets.iter_mut().zip(render_target_blends.iter()) {
         target.RenderTargetWriteMask = color_desc.mask.bits() as _;
         if let Some(ref blend) = color_desc.blend {
             let (color_op, color_src, color_dst) = map_blend_op(blend.color);
@@ -651,13 +671,72 @@ pub(crate) fn map_blend_desc(
     desc: &BlendDesc,
     multisampling: &Option<Multisampling>,
 ) -> D3D11_BLEND_DESC {
+    // Independent blending requires feature level 10_1, only enable it when
+    // the targets actually differ.
+    let independent = desc
+        .targets
+        .iter()
+        .skip(1)
+        .any(|target| target != &desc.targets[0]);
     D3D11_BLEND_DESC {
         AlphaToCoverageEnable: multisampling.as_ref().map_or(false, |m| m.alpha_coverage) as _,
-        IndependentBlendEnable: TRUE,
+        IndependentBlendEnable: independent as _,
         RenderTarget: map_blend_targets(&desc.targets),
     }
 }
 
+fn map_logic_op(op: &LogicOp) -> D3D11_LOGIC_OP {
+    match *op {
+        LogicOp::Clear => D3D11_LOGIC_OP_CLEAR,
+        LogicOp::And => D3D11_LOGIC_OP_AND,
+        LogicOp::AndReverse => D3D11_LOGIC_OP_AND_REVERSE,
+        LogicOp::Copy => D3D11_LOGIC_OP_COPY,
+        LogicOp::AndInverted => D3D11_LOGIC_OP_AND_INVERTED,
+        LogicOp::NoOp => D3D11_LOGIC_OP_NOOP,
+        LogicOp::Xor => D3D11_LOGIC_OP_XOR,
+        LogicOp::Or => D3D11_LOGIC_OP_OR,
+        LogicOp::Nor => D3D11_LOGIC_OP_NOR,
+        LogicOp::Equivalent => D3D11_LOGIC_OP_EQUIV,
+        LogicOp::Invert => D3D11_LOGIC_OP_INVERT,
+        LogicOp::OrReverse => D3D11_LOGIC_OP_OR_REVERSE,
+        LogicOp::CopyInverted => D3D11_LOGIC_OP_COPY_INVERTED,
+        LogicOp::OrInverted => D3D11_LOGIC_OP_OR_INVERTED,
+        LogicOp::Nand => D3D11_LOGIC_OP_NAND,
+        LogicOp::Set => D3D11_LOGIC_OP_SET,
+    }
+}
+
+/// Blend description for `ID3D11Device1::CreateBlendState1`, used when a logic
+/// operation is requested. Logic ops can't be combined with blending or
+/// independent blend states, so only the write masks are kept.
+pub(crate) fn map_blend_desc1(
+    desc: &BlendDesc,
+    multisampling: &Option<Multisampling>,
+) -> D3D11_BLEND_DESC1 {
+    let mut targets: [D3D11_RENDER_TARGET_BLEND_DESC1; 8] = [unsafe { mem::zeroed() }; 8];
+    let logic_op = desc.logic_op.as_ref().map(map_logic_op);
+
+    for (target, color_desc) in targets.iter_mut().zip(desc.targets.iter()) {
+        target.RenderTargetWriteMask = color_desc.mask.bits() as _;
+        target.SrcBlend = D3D11_BLEND_ONE;
+        target.DestBlend = D3D11_BLEND_ZERO;
+        target.BlendOp = D3D11_BLEND_OP_ADD;
+        target.SrcBlendAlpha = D3D11_BLEND_ONE;
+        target.DestBlendAlpha = D3D11_BLEND_ZERO;
+        target.BlendOpAlpha = D3D11_BLEND_OP_ADD;
+        if let Some(op) = logic_op {
+            target.LogicOpEnable = TRUE;
+            target.LogicOp = op;
+        }
+    }
+
+    D3D11_BLEND_DESC1 {
+        AlphaToCoverageEnable: multisampling.as_ref().map_or(false, |m| m.alpha_coverage) as _,
+        IndependentBlendEnable: FALSE,
+        RenderTarget: targets,
+    }
+}
+
 pub fn map_comparison(func: Comparison) -> D3D11_COMPARISON_FUNC {
     match func {
         Comparison::Never => D3D11_COMPARISON_NEVER,
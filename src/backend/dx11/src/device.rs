@@ -5,7 +5,12 @@ use hal::{
 };
 
 use winapi::{
-    shared::{dxgi, dxgiformat, dxgitype, minwindef::TRUE, windef::HWND, winerror},
+    shared::{
+        dxgi, dxgiformat, dxgitype,
+        minwindef::{FALSE, TRUE},
+        windef::HWND,
+        winerror::{self, HRESULT},
+    },
     um::{d3d11, d3d11_1, d3d11sdklayers, d3dcommon},
 };
 
@@ -53,6 +58,8 @@ pub struct Device {
     raw: ComPtr<d3d11::ID3D11Device>,
     raw1: Option<ComPtr<d3d11_1::ID3D11Device1>>,
     pub(crate) context: ComPtr<d3d11::ID3D11DeviceContext>,
+    /// Serializes access to the immediate context, which is shared with the queue.
+    pub(crate) context_lock: Arc<Mutex<()>>,
     features: hal::Features,
     memory_properties: MemoryProperties,
     render_doc: gfx_renderdoc::RenderDoc,
@@ -98,12 +105,73 @@ impl Device {
             raw: device,
             raw1: device1,
             context,
+            context_lock: Arc::new(Mutex::new(())),
             features,
             memory_properties,
             render_doc: Default::default(),
         }
     }
 
+    /// Query the timestamp frequency, which D3D11 only reports through a disjoint query.
+    pub(crate) fn query_timestamp_period(&self) -> f32 {
+        let desc = d3d11::D3D11_QUERY_DESC {
+            Query: d3d11::D3D11_QUERY_TIMESTAMP_DISJOINT,
+            MiscFlags: 0,
+        };
+        let mut data = d3d11::D3D11_QUERY_DATA_TIMESTAMP_DISJOINT {
+            Frequency: 0,
+            Disjoint: FALSE,
+        };
+        unsafe {
+            let mut query = ptr::null_mut();
+            if !winerror::SUCCEEDED(self.raw.CreateQuery(&desc, &mut query)) {
+                return 1.0;
+            }
+            let query = ComPtr::<d3d11::ID3D11Query>::from_raw(query);
+            let raw = query.as_raw() as *mut d3d11::ID3D11Asynchronous;
+
+            let _guard = self.context_lock.lock();
+            self.context.Begin(raw);
+            self.context.End(raw);
+            loop {
+                match self.context.GetData(
+                    raw,
+                    &mut data as *mut _ as *mut _,
+                    mem::size_of::<d3d11::D3D11_QUERY_DATA_TIMESTAMP_DISJOINT>() as _,
+                    0,
+                ) {
+                    winerror::S_FALSE => std::thread::yield_now(),
+                    winerror::S_OK => break,
+                    _ => return 1.0,
+                }
+            }
+        }
+
+        if data.Frequency == 0 {
+            1.0
+        } else {
+            (1_000_000_000.0 / data.Frequency as f64) as f32
+        }
+    }
+
+    /// Check whether the event query of a submitted fence has completed.
+    unsafe fn is_fence_query_done(&self, fence: &Fence) -> bool {
+        // The context lock is always taken before the pending flag.
+        let _guard = self.context_lock.lock();
+        let mut pending = fence.pending.lock();
+        if *pending
+            && self.context.GetData(
+                fence.query.as_raw() as *mut d3d11::ID3D11Asynchronous,
+                ptr::null_mut(),
+                0,
+                0,
+            ) == winerror::S_OK
+        {
+            *pending = false;
+        }
+        !*pending
+    }
+
     pub fn as_raw(&self) -> *mut d3d11::ID3D11Device {
         self.raw.as_raw()
     }
@@ -134,11 +202,27 @@ impl Device {
         multisampling: &Option<pso::Multisampling>,
     ) -> Result<ComPtr<d3d11::ID3D11BlendState>, pso::CreationError> {
         let mut blend = ptr::null_mut();
-        let desc = conv::map_blend_desc(blend_desc, multisampling);
 
-        let hr = unsafe {
-            self.raw
-                .CreateBlendState(&desc, &mut blend as *mut *mut _ as *mut *mut _)
+        let hr = match (&blend_desc.logic_op, &self.raw1) {
+            (Some(_), Some(_)) if blend_desc.targets.iter().any(|t| t.blend.is_some()) => {
+                // D3D11 disables blending on every target with a logic op enabled.
+                error!("Logic ops can't be combined with blending");
+                return Err(pso::CreationError::UnsupportedPipeline);
+            }
+            (Some(_), Some(device1)) => {
+                let desc = conv::map_blend_desc1(blend_desc, multisampling);
+                unsafe {
+                    device1.CreateBlendState1(&desc, &mut blend as *mut *mut _ as *mut *mut _)
+                }
+            }
+            (Some(_), None) => return Err(pso::CreationError::UnsupportedPipeline),
+            (None, _) => {
+                let desc = conv::map_blend_desc(blend_desc, multisampling);
+                unsafe {
+                    self.raw
+                        .CreateBlendState(&desc, &mut blend as *mut *mut _ as *mut *mut _)
+                }
+            }
         };
 
         if winerror::SUCCEEDED(hr) {
@@ -466,6 +550,8 @@ impl Device {
         desc.Format = info.format;
         if desc.Format == dxgiformat::DXGI_FORMAT_D32_FLOAT_S8X24_UINT {
             desc.Format = dxgiformat::DXGI_FORMAT_R32_FLOAT_X8X24_TYPELESS;
+        } else if desc.Format == dxgiformat::DXGI_FORMAT_D24_UNORM_S8_UINT {
+            desc.Format = dxgiformat::DXGI_FORMAT_R24_UNORM_X8_TYPELESS;
         }
 
         #[allow(non_snake_case)]
@@ -855,10 +941,137 @@ impl Device {
 
             unsafe { ComPtr::from_raw(swapchain) }
         };
+
+        // Bound how many frames the CPU may queue ahead of the GPU to the
+        // number of swapchain images, matching the DX12 backend.
+        if let Ok(dxgi_device) = self.raw.cast::<dxgi::IDXGIDevice1>() {
+            unsafe {
+                dxgi_device.SetMaximumFrameLatency(config.image_count);
+            }
+        }
+
         Ok((dxgi_swapchain, non_srgb_format))
     }
 }
 
+/// Reads the result of a single query, returning its values and whether they are available.
+///
+/// `get_data` forwards to `ID3D11DeviceContext::GetData` on the immediate context.
+pub(crate) unsafe fn read_query<F>(
+    pool: &QueryPool,
+    id: query::Id,
+    flags: query::ResultFlags,
+    get_data: F,
+) -> Result<([u64; 11], bool), device::WaitError>
+where
+    F: Fn(*mut d3d11::ID3D11Asynchronous, *mut winapi::ctypes::c_void, u32) -> HRESULT,
+{
+    let wait = |raw, data, size| loop {
+        let hr = get_data(raw, data, size);
+        if hr != winerror::S_FALSE || !flags.contains(query::ResultFlags::WAIT) {
+            break hr;
+        }
+        std::thread::yield_now();
+    };
+
+    let data_size = match pool.ty {
+        query::Type::Occlusion | query::Type::Timestamp => mem::size_of::<u64>(),
+        query::Type::PipelineStatistics(_) => {
+            mem::size_of::<d3d11::D3D11_QUERY_DATA_PIPELINE_STATISTICS>()
+        }
+    };
+    let mut values = [0u64; 11];
+    let hr = wait(
+        pool.as_async(id),
+        values.as_mut_ptr() as *mut _,
+        data_size as _,
+    );
+
+    let mut available = match hr {
+        winerror::S_OK => true,
+        winerror::S_FALSE => false,
+        winerror::DXGI_ERROR_DEVICE_REMOVED => {
+            return Err(device::DeviceLost.into());
+        }
+        _ => return Err(device::OutOfMemory::Device.into()),
+    };
+    if available && !pool.disjoint.is_empty() {
+        // The bracketing disjoint query ends right after the timestamp.
+        let mut disjoint = d3d11::D3D11_QUERY_DATA_TIMESTAMP_DISJOINT {
+            Frequency: 0,
+            Disjoint: FALSE,
+        };
+        let hr = wait(
+            pool.disjoint_async(id),
+            &mut disjoint as *mut _ as *mut _,
+            mem::size_of::<d3d11::D3D11_QUERY_DATA_TIMESTAMP_DISJOINT>() as _,
+        );
+        if hr != winerror::S_OK {
+            available = false;
+        } else if disjoint.Disjoint != FALSE {
+            warn!(
+                "Timestamp query {} is invalid, the counter was disjoint",
+                id
+            );
+            available = false;
+        }
+    }
+
+    Ok((values, available))
+}
+
+/// Writes a query result as laid out by `get_query_pool_results`, returning the
+/// number of bytes covered.
+pub(crate) unsafe fn write_query_result(
+    dst: &mut [u8],
+    ty: query::Type,
+    values: &[u64; 11],
+    available: bool,
+    flags: query::ResultFlags,
+) -> usize {
+    // Pipeline statistics are laid out in the same order as the bits of
+    // `query::PipelineStatistic`, other query types return a single value.
+    let value_mask = match ty {
+        query::Type::Occlusion | query::Type::Timestamp => 0x1,
+        query::Type::PipelineStatistics(statistics) => statistics.bits(),
+    };
+    let mut num_values = 0;
+    for (bit, &value) in values.iter().enumerate() {
+        if value_mask & (1 << bit) == 0 {
+            continue;
+        }
+        if available || flags.contains(query::ResultFlags::PARTIAL) {
+            write_query_value(dst, num_values, value, flags);
+        }
+        num_values += 1;
+    }
+    if flags.contains(query::ResultFlags::WITH_AVAILABILITY) {
+        write_query_value(dst, num_values, available as u64, flags);
+        num_values += 1;
+    }
+    let value_size = if flags.contains(query::ResultFlags::BITS_64) {
+        8
+    } else {
+        4
+    };
+    num_values * value_size
+}
+
+/// Write the `index`-th value of a query result, honoring the requested result width.
+unsafe fn write_query_value(dst: &mut [u8], index: usize, value: u64, flags: query::ResultFlags) {
+    if flags.contains(query::ResultFlags::BITS_64) {
+        assert!(dst.len() >= (index + 1) * 8);
+        (dst.as_mut_ptr() as *mut u64)
+            .add(index)
+            .write_unaligned(value);
+    } else {
+        assert!(dst.len() >= (index + 1) * 4);
+        (dst.as_mut_ptr() as *mut u32)
+            .add(index)
+            .write_unaligned(value as u32);
+    }
+}
+
 impl device::Device<Backend> for Device {
     unsafe fn allocate_memory(
         &self,
@@ -1536,6 +1749,24 @@ impl device::Device<Backend> for Device {
         );
         let initial_data_ptr = ptr::null_mut();
 
+        // `GenerateMips` needs the texture to be both renderable and sampled.
+        let generate_mips = {
+            let mut support = 0;
+            let hr = self.raw.CheckFormatSupport(dxgi_format, &mut support);
+            winerror::SUCCEEDED(hr)
+                && support & d3d11::D3D11_FORMAT_SUPPORT_MIP_AUTOGEN != 0
+                && usage == d3d11::D3D11_USAGE_DEFAULT
+                && image.mip_levels > 1
+                && image
+                    .usage
+                    .contains(Usage::SAMPLED | Usage::COLOR_ATTACHMENT)
+        };
+        let misc_flags = if generate_mips {
+            d3d11::D3D11_RESOURCE_MISC_GENERATE_MIPS
+        } else {
+            0
+        };
+
         let mut resource = ptr::null_mut();
         let view_kind = match image.kind {
             image::Kind::D1(width, layers) => {
@@ -1547,7 +1778,7 @@ impl device::Device<Backend> for Device {
                     Usage: usage,
                     BindFlags: bind,
                     CPUAccessFlags: cpu,
-                    MiscFlags: 0,
+                    MiscFlags: misc_flags,
                 };
 
                 let hr = self.raw.CreateTexture1D(
@@ -1579,7 +1810,7 @@ impl device::Device<Backend> for Device {
                     BindFlags: bind,
                     CPUAccessFlags: cpu,
                     MiscFlags: {
-                        let mut flags = 0;
+                        let mut flags = misc_flags;
                         if image.view_caps.contains(image::ViewCapabilities::KIND_CUBE) {
                             flags |= d3d11::D3D11_RESOURCE_MISC_TEXTURECUBE;
                         }
@@ -1611,7 +1842,7 @@ impl device::Device<Backend> for Device {
                     Usage: usage,
                     BindFlags: bind,
                     CPUAccessFlags: cpu,
-                    MiscFlags: 0,
+                    MiscFlags: misc_flags,
                 };
 
                 let hr = self.raw.CreateTexture3D(
@@ -1821,7 +2052,7 @@ impl device::Device<Backend> for Device {
         };
 
         let srv_info = ViewInfo {
-            format: conv::viewable_format(info.format),
+            format: conv::viewable_format(info.format, range.aspects),
             ..info.clone()
         };
 
@@ -2185,6 +2416,7 @@ impl device::Device<Backend> for Device {
     where
         I: Iterator<Item = (&'a Memory, memory::Segment)>,
     {
+        let _guard = self.context_lock.lock();
         let _scope = debug_scope!(&self.context, "FlushMappedRanges");
 
         // go through every range we wrote to
@@ -2204,6 +2436,7 @@ impl device::Device<Backend> for Device {
     where
         I: Iterator<Item = (&'a Memory, memory::Segment)>,
     {
+        let _guard = self.context_lock.lock();
         let _scope = debug_scope!(&self.context, "InvalidateMappedRanges");
 
         // go through every range we want to read from
@@ -2227,14 +2460,27 @@ impl device::Device<Backend> for Device {
     }
 
     fn create_fence(&self, signalled: bool) -> Result<Fence, device::OutOfMemory> {
+        let desc = d3d11::D3D11_QUERY_DESC {
+            Query: d3d11::D3D11_QUERY_EVENT,
+            MiscFlags: 0,
+        };
+        let mut query = ptr::null_mut();
+        let hr = unsafe { self.raw.CreateQuery(&desc, &mut query) };
+        if !winerror::SUCCEEDED(hr) {
+            return Err(device::OutOfMemory::Device);
+        }
+
         Ok(Arc::new(RawFence {
             mutex: Mutex::new(signalled),
             condvar: Condvar::new(),
+            query: unsafe { ComPtr::from_raw(query) },
+            pending: Mutex::new(false),
         }))
     }
 
     unsafe fn reset_fence(&self, fence: &mut Fence) -> Result<(), device::OutOfMemory> {
         *fence.mutex.lock() = false;
+        *fence.pending.lock() = false;
         Ok(())
     }
 
@@ -2246,35 +2492,51 @@ impl device::Device<Backend> for Device {
         use std::time::{Duration, Instant};
 
         debug!("wait_for_fence {:?} for {} ns", fence, timeout_ns);
-        let mut guard = fence.mutex.lock();
-        match timeout_ns {
-            0 => Ok(*guard),
-            0xFFFFFFFFFFFFFFFF => {
-                while !*guard {
-                    fence.condvar.wait(&mut guard);
+        let total = Duration::from_nanos(timeout_ns as u64);
+        let now = Instant::now();
+        {
+            let mut guard = fence.mutex.lock();
+            while !*guard {
+                match timeout_ns {
+                    0 => return Ok(false),
+                    0xFFFFFFFFFFFFFFFF => fence.condvar.wait(&mut guard),
+                    _ => {
+                        let duration = match total.checked_sub(now.elapsed()) {
+                            Some(dur) => dur,
+                            None => return Ok(false),
+                        };
+                        let result = fence.condvar.wait_for(&mut guard, duration);
+                        if result.timed_out() {
+                            return Ok(false);
+                        }
+                    }
                 }
-                Ok(true)
             }
-            _ => {
-                let total = Duration::from_nanos(timeout_ns as u64);
-                let now = Instant::now();
-                while !*guard {
-                    let duration = match total.checked_sub(now.elapsed()) {
-                        Some(dur) => dur,
-                        None => return Ok(false),
-                    };
-                    let result = fence.condvar.wait_for(&mut guard, duration);
-                    if result.timed_out() {
-                        return Ok(false);
+        }
+
+        // The fence was submitted, now wait for the GPU to get through the work.
+        // D3D11 can't block on a query, so poll it with an increasing back-off.
+        let mut backoff = Duration::from_micros(10);
+        while !self.is_fence_query_done(fence) {
+            let sleep = if timeout_ns == 0xFFFFFFFFFFFFFFFF {
+                backoff
+            } else {
+                match total.checked_sub(now.elapsed()) {
+                    Some(remaining) if remaining > Duration::from_nanos(0) => {
+                        backoff.min(remaining)
                     }
+                    _ => return Ok(false),
                 }
-                Ok(true)
-            }
+            };
+            std::thread::sleep(sleep);
+            backoff = (backoff * 2).min(Duration::from_millis(1));
         }
+        Ok(true)
     }
 
     unsafe fn get_fence_status(&self, fence: &Fence) -> Result<bool, device::DeviceLost> {
-        Ok(*fence.mutex.lock())
+        let submitted = *fence.mutex.lock();
+        Ok(submitted && self.is_fence_query_done(fence))
     }
 
     fn create_event(&self) -> Result<(), device::OutOfMemory> {
@@ -2307,25 +2569,72 @@ impl device::Device<Backend> for Device {
 
     unsafe fn create_query_pool(
         &self,
-        _query_ty: query::Type,
-        _count: query::Id,
+        query_ty: query::Type,
+        count: query::Id,
     ) -> Result<QueryPool, query::CreationError> {
-        unimplemented!()
+        let desc = d3d11::D3D11_QUERY_DESC {
+            Query: match query_ty {
+                query::Type::Occlusion => d3d11::D3D11_QUERY_OCCLUSION,
+                query::Type::Timestamp => d3d11::D3D11_QUERY_TIMESTAMP,
+                query::Type::PipelineStatistics(_) => d3d11::D3D11_QUERY_PIPELINE_STATISTICS,
+            },
+            MiscFlags: 0,
+        };
+
+        let create_queries = |desc: &d3d11::D3D11_QUERY_DESC| {
+            let mut queries = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let mut query = ptr::null_mut();
+                let hr = self.raw.CreateQuery(desc, &mut query);
+                if !winerror::SUCCEEDED(hr) {
+                    return Err(device::OutOfMemory::Device);
+                }
+                queries.push(ComPtr::from_raw(query));
+            }
+            Ok(queries)
+        };
+
+        let raw = create_queries(&desc)?;
+        let disjoint = match query_ty {
+            query::Type::Timestamp => create_queries(&d3d11::D3D11_QUERY_DESC {
+                Query: d3d11::D3D11_QUERY_TIMESTAMP_DISJOINT,
+                MiscFlags: 0,
+            })?,
+            _ => Vec::new(),
+        };
+
+        Ok(QueryPool {
+            ty: query_ty,
+            raw,
+            disjoint,
+        })
     }
 
     unsafe fn destroy_query_pool(&self, _pool: QueryPool) {
-        unimplemented!()
+        // Just drop
     }
 
     unsafe fn get_query_pool_results(
         &self,
-        _pool: &QueryPool,
-        _queries: Range<query::Id>,
-        _data: &mut [u8],
-        _stride: buffer::Stride,
-        _flags: query::ResultFlags,
+        pool: &QueryPool,
+        queries: Range<query::Id>,
+        data: &mut [u8],
+        stride: buffer::Stride,
+        flags: query::ResultFlags,
     ) -> Result<bool, device::WaitError> {
-        unimplemented!()
+        let mut is_ready = true;
+
+        for (i, id) in queries.enumerate() {
+            let (values, available) = read_query(pool, id, flags, |raw, out, size| {
+                let _guard = self.context_lock.lock();
+                self.context.GetData(raw, out, size, 0)
+            })?;
+            is_ready &= available;
+            let dst = &mut data[i * stride as usize..];
+            write_query_result(dst, pool.ty, &values, available, flags);
+        }
+
+        Ok(is_ready)
     }
 
     unsafe fn destroy_shader_module(&self, _shader_lib: ShaderModule) {}
@@ -2400,7 +2709,7 @@ impl device::Device<Backend> for Device {
         //unimplemented!()
     }
 
-    fn wait_idle(&self) -> Result<(), device::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), device::WaitError> {
         Ok(())
         // unimplemented!()
     }
@@ -34,12 +34,12 @@ use smallvec::SmallVec;
 use winapi::{
     shared::{
         dxgi::{IDXGIAdapter, IDXGIFactory, IDXGISwapChain},
-        dxgiformat,
+        dxgi1_4, dxgiformat,
         minwindef::{FALSE, HMODULE, UINT},
         windef::{HWND, RECT},
         winerror,
     },
-    um::{d3d11, d3d11_1, d3dcommon, winuser::GetClientRect},
+    um::{d3d11, d3d11_1, d3dcommon, winnt, winuser::GetClientRect},
     Interface as _,
 };
 
@@ -169,7 +169,6 @@ fn get_features(
     let mut features = hal::Features::empty()
         | hal::Features::ROBUST_BUFFER_ACCESS // TODO: verify
         | hal::Features::INSTANCE_RATE
-        | hal::Features::INDEPENDENT_BLENDING // TODO: verify
         | hal::Features::SAMPLER_BORDER_COLOR
         | hal::Features::SAMPLER_MIP_LOD_BIAS
         | hal::Features::SAMPLER_MIRROR_CLAMP_EDGE
@@ -202,13 +201,17 @@ fn get_features(
     if feature_level >= d3dcommon::D3D_FEATURE_LEVEL_10_0 {
         features |= hal::Features::TEXTURE_DESCRIPTOR_ARRAY
             | hal::Features::FULL_DRAW_INDEX_U32
-            | hal::Features::GEOMETRY_SHADER;
+            | hal::Features::GEOMETRY_SHADER
+            | hal::Features::MULTI_VIEWPORTS
+            | hal::Features::DUAL_SRC_BLENDING
+            | hal::Features::PRECISE_OCCLUSION_QUERY
+            | hal::Features::PIPELINE_STATISTICS_QUERY;
         downlevel.shader_model = hal::DownlevelShaderModel::ShaderModel4;
         downlevel.non_power_of_two_mipmapped_textures = true;
     }
 
     if feature_level >= d3dcommon::D3D_FEATURE_LEVEL_10_1 {
-        features |= hal::Features::IMAGE_CUBE_ARRAY;
+        features |= hal::Features::IMAGE_CUBE_ARRAY | hal::Features::INDEPENDENT_BLENDING;
     }
 
     if feature_level >= d3dcommon::D3D_FEATURE_LEVEL_11_0 {
@@ -225,8 +228,18 @@ fn get_features(
         downlevel.device_local_image_copies = true;
     }
 
-    if feature_level >= d3dcommon::D3D_FEATURE_LEVEL_11_1 {
-        features |= hal::Features::LOGIC_OP; // TODO: Optional at 10_0 -> 11_0
+    // The D3D11_OPTIONS query is only understood by the 11.1 runtime, so a failure
+    // here just means logic ops aren't available.
+    let mut options: d3d11::D3D11_FEATURE_DATA_D3D11_OPTIONS = unsafe { mem::zeroed() };
+    let hr = unsafe {
+        device.CheckFeatureSupport(
+            d3d11::D3D11_FEATURE_D3D11_OPTIONS,
+            &mut options as *mut _ as *mut _,
+            mem::size_of::<d3d11::D3D11_FEATURE_DATA_D3D11_OPTIONS>() as _,
+        )
+    };
+    if winerror::SUCCEEDED(hr) && options.OutputMergerLogicOp != 0 {
+        features |= hal::Features::LOGIC_OP;
     }
 
     (features, downlevel, performance)
@@ -296,6 +309,14 @@ fn get_limits(feature_level: d3dcommon::D3D_FEATURE_LEVEL) -> hal::Limits {
 
     let max_constant_buffers = d3d11::D3D11_COMMONSHADER_CONSTANT_BUFFER_API_SLOT_COUNT - 1;
 
+    // Hull and domain shaders are only available from feature level 11_0.
+    let max_patch_size = match feature_level {
+        d3dcommon::D3D_FEATURE_LEVEL_11_0 | d3dcommon::D3D_FEATURE_LEVEL_11_1 => {
+            d3d11::D3D11_IA_PATCH_MAX_CONTROL_POINT_COUNT as _
+        }
+        _ => 0,
+    };
+
     let (
         max_compute_work_group_count_z,
         max_compute_work_group_size_xy,
@@ -340,7 +361,7 @@ fn get_limits(feature_level: d3dcommon::D3D_FEATURE_LEVEL) -> hal::Limits {
         },
         max_bound_descriptor_sets: pso::DescriptorSetIndex::MAX,
         max_texel_elements: max_texture_uv_dimension as _, //TODO
-        max_patch_size: d3d11::D3D11_IA_PATCH_MAX_CONTROL_POINT_COUNT as _,
+        max_patch_size,
         max_viewports: d3d11::D3D11_VIEWPORT_AND_SCISSORRECT_OBJECT_COUNT_PER_PIPELINE as _,
         max_viewport_dimensions: [d3d11::D3D11_VIEWPORT_BOUNDS_MAX; 2],
         max_framebuffer_extent: hal::image::Extent {
@@ -384,6 +405,7 @@ fn get_limits(feature_level: d3dcommon::D3D_FEATURE_LEVEL) -> hal::Limits {
         min_vertex_input_binding_stride_alignment: 1,
         max_push_constants_size: MAX_PUSH_CONSTANT_SIZE,
         max_uniform_buffer_range: 1 << 16,
+        timestamp_compute_and_graphics: true,
         ..hal::Limits::default() //TODO
     }
 }
@@ -628,7 +650,6 @@ impl hal::Instance<Backend> for Instance {
                     dynamic_pipeline_states: hal::DynamicStates::VIEWPORT
                         | hal::DynamicStates::SCISSOR
                         | hal::DynamicStates::BLEND_CONSTANTS
-                        | hal::DynamicStates::DEPTH_BOUNDS
                         | hal::DynamicStates::STENCIL_REFERENCE,
                     downlevel,
                     performance_caveats,
@@ -696,6 +717,32 @@ impl fmt::Debug for PhysicalDevice {
 unsafe impl Send for PhysicalDevice {}
 unsafe impl Sync for PhysicalDevice {}
 
+impl PhysicalDevice {
+    /// Register `event` to be signaled whenever the budget reported by `memory_budget`
+    /// changes, so it doesn't need to be polled.
+    ///
+    /// Returns the cookie for `unregister_memory_budget_event`, or `None` if the
+    /// adapter doesn't support budget notifications.
+    pub unsafe fn register_memory_budget_event(&self, event: winnt::HANDLE) -> Option<u32> {
+        let adapter3 = self.adapter.cast::<dxgi1_4::IDXGIAdapter3>().ok()?;
+        let mut cookie = 0;
+        let hr = adapter3.RegisterVideoMemoryBudgetChangeNotificationEvent(event, &mut cookie);
+        if winerror::SUCCEEDED(hr) {
+            Some(cookie)
+        } else {
+            error!("error on memory budget registration 0x{:x}", hr);
+            None
+        }
+    }
+
+    /// Stop signaling the event registered with `register_memory_budget_event`.
+    pub unsafe fn unregister_memory_budget_event(&self, cookie: u32) {
+        if let Ok(adapter3) = self.adapter.cast::<dxgi1_4::IDXGIAdapter3>() {
+            adapter3.UnregisterVideoMemoryBudgetChangeNotification(cookie);
+        }
+    }
+}
+
 // TODO: does the adapter we get earlier matter for feature level?
 fn get_feature_level(func: &CreateFun, adapter: *mut IDXGIAdapter) -> d3dcommon::D3D_FEATURE_LEVEL {
     let requested_feature_levels = [
@@ -851,6 +898,8 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             feature_level,
         );
 
+        let timestamp_period = device.query_timestamp_period();
+
         // TODO: deferred context => 1 cxt/queue?
         let queue_groups = families
             .iter()
@@ -861,6 +910,8 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
                 // TODO: multiple queues?
                 let queue = Queue {
                     context: device.context.clone(),
+                    context_lock: Arc::clone(&device.context_lock),
+                    timestamp_period,
                 };
                 group.add_queue(queue);
                 group
@@ -1013,6 +1064,33 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         self.properties
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        let adapter3 = unsafe { self.adapter.cast::<dxgi1_4::IDXGIAdapter3>() }.ok()?;
+
+        // The first heap is the video memory, the second one the system memory.
+        let segments = [
+            dxgi1_4::DXGI_MEMORY_SEGMENT_GROUP_LOCAL,
+            dxgi1_4::DXGI_MEMORY_SEGMENT_GROUP_NON_LOCAL,
+        ];
+        let mut budgets = Vec::with_capacity(self.memory_properties.memory_heaps.len());
+        for &segment in segments
+            .iter()
+            .take(self.memory_properties.memory_heaps.len())
+        {
+            let mut mem_info: dxgi1_4::DXGI_QUERY_VIDEO_MEMORY_INFO = unsafe { mem::zeroed() };
+            let hr = unsafe { adapter3.QueryVideoMemoryInfo(0, segment, &mut mem_info) };
+            if !winerror::SUCCEEDED(hr) {
+                error!("error on video memory query 0x{:x}", hr);
+                return None;
+            }
+            budgets.push(adapter::MemoryHeapBudget {
+                budget: mem_info.Budget,
+                usage: mem_info.CurrentUsage,
+            });
+        }
+        Some(budgets)
+    }
+
     unsafe fn enumerate_displays(&self) -> Vec<display::Display<crate::Backend>> {
         unimplemented!();
     }
@@ -1092,6 +1170,7 @@ impl window::Surface<Backend> for Surface {
             present_modes: window::PresentMode::IMMEDIATE | window::PresentMode::FIFO,
             composite_alpha_modes: window::CompositeAlphaMode::OPAQUE, //TODO
             image_count: 1..=16,                                       // TODO:
+            color_spaces: window::ColorSpace::SRGB_NONLINEAR,
             current_extent,
             extents: window::Extent2D {
                 width: 16,
@@ -1142,6 +1221,9 @@ impl window::PresentationSurface<Backend> for Surface {
         config: window::SwapchainConfig,
     ) -> Result<(), window::SwapchainError> {
         assert!(image::Usage::COLOR_ATTACHMENT.contains(config.image_usage));
+        if config.color_space != window::ColorSpace::SRGB_NONLINEAR {
+            return Err(window::SwapchainError::UnsupportedColorSpace);
+        }
 
         let swapchain = match self.presentation.take() {
             Some(present) => {
@@ -1162,6 +1244,7 @@ impl window::PresentationSurface<Backend> for Surface {
                 );
                 present_image.internal.release_resources();
 
+                let _guard = device.context_lock.lock();
                 let result = present.swapchain.ResizeBuffers(
                     config.image_count,
                     config.extent.width,
@@ -1302,6 +1385,9 @@ impl queue::QueueFamily for QueueFamily {
 #[derive(Clone)]
 pub struct Queue {
     context: ComPtr<d3d11::ID3D11DeviceContext>,
+    /// Shared with the device, guards the immediate context.
+    context_lock: Arc<Mutex<()>>,
+    timestamp_period: f32,
 }
 
 impl fmt::Debug for Queue {
@@ -1323,6 +1409,7 @@ impl queue::Queue<Backend> for Queue {
     ) where
         Ic: Iterator<Item = &'a CommandBuffer>,
     {
+        let _guard = self.context_lock.lock();
         let _scope = debug_scope!(&self.context, "Submit(fence={:?})", fence);
         for cmd_buf in command_buffers {
             let _scope = debug_scope!(
@@ -1340,6 +1427,9 @@ impl queue::Queue<Backend> for Queue {
             }
             self.context
                 .ExecuteCommandList(cmd_buf.as_raw_list().as_raw(), FALSE);
+            for copy in &cmd_buf.query_copies {
+                copy.execute(&self.context);
+            }
             {
                 let _scope = debug_scope!(&self.context, "Post-Exec: Invalidate");
                 for sync in &cmd_buf.invalidate_coherent_memory {
@@ -1349,6 +1439,11 @@ impl queue::Queue<Backend> for Queue {
         }
 
         if let Some(fence) = fence {
+            // Track the GPU completion of the submission with the fence's event query.
+            self.context
+                .End(fence.query.as_raw() as *mut d3d11::ID3D11Asynchronous);
+            *fence.pending.lock() = true;
+
             *fence.mutex.lock() = true;
             fence.condvar.notify_all();
         }
@@ -1369,17 +1464,29 @@ impl queue::Queue<Backend> for Queue {
             _ => (0, 0),
         };
         presentation.is_init = false;
-        presentation.swapchain.Present(interval, flags);
-        Ok(None)
+        let _guard = self.context_lock.lock();
+        match presentation.swapchain.Present(interval, flags) {
+            winerror::DXGI_ERROR_DEVICE_REMOVED
+            | winerror::DXGI_ERROR_DEVICE_RESET
+            | winerror::DXGI_ERROR_DEVICE_HUNG
+            | winerror::DXGI_ERROR_DRIVER_INTERNAL_ERROR => Err(hal::device::DeviceLost.into()),
+            winerror::E_OUTOFMEMORY => Err(hal::device::OutOfMemory::Host.into()),
+            hr if !winerror::SUCCEEDED(hr) => {
+                // Anything else leaves the swapchain unusable, so have it re-created.
+                error!("Present failed: 0x{:x}", hr);
+                Err(window::OutOfDate.into())
+            }
+            _ => Ok(None),
+        }
     }
 
-    fn wait_idle(&mut self) -> Result<(), hal::device::OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), hal::device::WaitError> {
         // unimplemented!()
         Ok(())
     }
 
     fn timestamp_period(&self) -> f32 {
-        1.0
+        self.timestamp_period
     }
 }
 
@@ -1545,6 +1652,7 @@ pub struct CommandBufferState {
     // the highest binding number in currently bound pipeline
     max_bindings: Option<u32>,
     viewports: Vec<d3d11::D3D11_VIEWPORT>,
+    scissors: Vec<d3d11::D3D11_RECT>,
     vertex_buffers: Vec<*mut d3d11::ID3D11Buffer>,
     vertex_offsets: Vec<u32>,
     vertex_strides: Vec<u32>,
@@ -1578,6 +1686,7 @@ impl CommandBufferState {
             required_bindings: None,
             max_bindings: None,
             viewports: Vec::new(),
+            scissors: Vec::new(),
             vertex_buffers: Vec::new(),
             vertex_offsets: Vec::new(),
             vertex_strides: Vec::new(),
@@ -1653,13 +1762,26 @@ impl CommandBufferState {
         }
     }
 
-    pub fn set_viewports(&mut self, viewports: &[d3d11::D3D11_VIEWPORT]) {
-        self.viewports.clear();
-        self.viewports.extend(viewports);
+    pub fn set_viewports(&mut self, first_viewport: u32, viewports: &[d3d11::D3D11_VIEWPORT]) {
+        let start = first_viewport as usize;
+        let end = start + viewports.len();
+        if self.viewports.len() < end {
+            self.viewports.resize(end, unsafe { mem::zeroed() });
+        }
+        self.viewports[start..end].copy_from_slice(viewports);
 
         self.dirty_flag.insert(DirtyStateFlag::VIEWPORTS);
     }
 
+    pub fn set_scissors(&mut self, first_scissor: u32, scissors: &[d3d11::D3D11_RECT]) {
+        let start = first_scissor as usize;
+        let end = start + scissors.len();
+        if self.scissors.len() < end {
+            self.scissors.resize(end, unsafe { mem::zeroed() });
+        }
+        self.scissors[start..end].copy_from_slice(scissors);
+    }
+
     pub fn bind_viewports(&mut self, context: &ComPtr<d3d11::ID3D11DeviceContext>) {
         if !self.dirty_flag.contains(DirtyStateFlag::VIEWPORTS) {
             return;
@@ -2081,6 +2203,10 @@ pub struct CommandBuffer {
     flush_coherent_memory: Vec<MemoryFlush>,
     invalidate_coherent_memory: Vec<MemoryInvalidate>,
 
+    // query results can't be written by the GPU, they are read back and uploaded
+    // to the destination buffers once the command list has been executed.
+    query_copies: Vec<QueryCopy>,
+
     // holds information about the active render pass
     render_pass_cache: Option<RenderPassCache>,
 
@@ -2159,6 +2285,7 @@ impl CommandBuffer {
             list: RefCell::new(None),
             flush_coherent_memory: Vec::new(),
             invalidate_coherent_memory: Vec::new(),
+            query_copies: Vec::new(),
             render_pass_cache: None,
             push_constant_data,
             push_constant_buffer,
@@ -2211,6 +2338,7 @@ impl CommandBuffer {
     fn reset(&mut self) {
         self.flush_coherent_memory.clear();
         self.invalidate_coherent_memory.clear();
+        self.query_copies.clear();
         self.render_pass_cache = None;
         self.cache.clear();
         self.debug_scopes.clear();
@@ -2419,15 +2547,33 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
     unsafe fn resolve_image<T>(
         &mut self,
-        _src: &Image,
+        src: &Image,
         _src_layout: image::Layout,
-        _dst: &Image,
+        dst: &Image,
         _dst_layout: image::Layout,
-        _regions: T,
+        regions: T,
     ) where
         T: Iterator<Item = command::ImageResolve>,
     {
-        unimplemented!()
+        let format = conv::map_format(dst.format).unwrap();
+
+        // D3D11 always resolves whole subresources, offsets and extents are ignored.
+        for region in regions {
+            let layers = region.src_subresource.layers.clone();
+            let dst_layer_start = region.dst_subresource.layers.start;
+            for (i, src_layer) in layers.enumerate() {
+                self.context.ResolveSubresource(
+                    dst.internal.raw,
+                    dst.calc_subresource(
+                        region.dst_subresource.level as _,
+                        (dst_layer_start as usize + i) as _,
+                    ),
+                    src.internal.raw,
+                    src.calc_subresource(region.src_subresource.level as _, src_layer as _),
+                    format,
+                );
+            }
+        }
     }
 
     unsafe fn blit_image<T>(
@@ -2451,6 +2597,19 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.cache.bind(&self.context);
     }
 
+    unsafe fn generate_mipmaps(&mut self, image: &Image) {
+        match image.internal.srv {
+            Some(ref srv)
+                if image
+                    .usage
+                    .contains(image::Usage::SAMPLED | image::Usage::COLOR_ATTACHMENT) =>
+            {
+                self.context.GenerateMips(srv.as_raw());
+            }
+            _ => error!("Mipmaps can only be generated for sampled and renderable images"),
+        }
+    }
+
     unsafe fn bind_index_buffer(&mut self, buffer: &Buffer, sub: buffer::SubRange, ty: IndexType) {
         self.context.IASetIndexBuffer(
             buffer.internal.raw,
@@ -2477,7 +2636,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.cache.bind_vertex_buffers(&self.context);
     }
 
-    unsafe fn set_viewports<T>(&mut self, _first_viewport: u32, viewports: T)
+    unsafe fn set_viewports<T>(&mut self, first_viewport: u32, viewports: T)
     where
         T: Iterator<Item = pso::Viewport>,
     {
@@ -2485,20 +2644,22 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
             .map(|ref vp| conv::map_viewport(vp))
             .collect::<SmallVec<[_; 4]>>();
 
-        // TODO: DX only lets us set all VPs at once, so cache in slice?
-        self.cache.set_viewports(&viewports);
+        // DX only lets us set all VPs at once, so they're cached and rebound together.
+        self.cache.set_viewports(first_viewport, &viewports);
         self.cache.bind_viewports(&self.context);
     }
 
-    unsafe fn set_scissors<T>(&mut self, _first_scissor: u32, scissors: T)
+    unsafe fn set_scissors<T>(&mut self, first_scissor: u32, scissors: T)
     where
         T: Iterator<Item = pso::Rect>,
     {
-        let scissors = scissors.map(|ref r| conv::map_rect(r)).collect::<Vec<_>>();
+        let scissors = scissors
+            .map(|ref r| conv::map_rect(r))
+            .collect::<SmallVec<[_; 4]>>();
 
-        // TODO: same as for viewports
+        self.cache.set_scissors(first_scissor, &scissors);
         self.context
-            .RSSetScissorRects(scissors.len() as _, scissors.as_ptr());
+            .RSSetScissorRects(self.cache.scissors.len() as _, self.cache.scissors.as_ptr());
     }
 
     unsafe fn set_blend_constants(&mut self, color: pso::ColorValue) {
@@ -2994,6 +3155,9 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         if src.is_coherent {
             self.defer_coherent_flush(src);
         }
+        if dst.is_coherent {
+            self.defer_coherent_invalidate(dst);
+        }
 
         for info in regions {
             let src_box = d3d11::D3D11_BOX {
@@ -3193,32 +3357,66 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn begin_query(&mut self, _query: query::Query<Backend>, _flags: query::ControlFlags) {
-        unimplemented!()
+    unsafe fn begin_query(&mut self, query: query::Query<Backend>, _flags: query::ControlFlags) {
+        self.context.Begin(query.pool.as_async(query.id));
     }
 
-    unsafe fn end_query(&mut self, _query: query::Query<Backend>) {
-        unimplemented!()
+    unsafe fn end_query(&mut self, query: query::Query<Backend>) {
+        self.context.End(query.pool.as_async(query.id));
     }
 
     unsafe fn reset_query_pool(&mut self, _pool: &QueryPool, _queries: Range<query::Id>) {
-        unimplemented!()
+        // Queries are implicitly reset when they are begun.
     }
 
     unsafe fn copy_query_pool_results(
         &mut self,
-        _pool: &QueryPool,
-        _queries: Range<query::Id>,
+        pool: &QueryPool,
+        queries: Range<query::Id>,
+        buffer: &Buffer,
+        offset: buffer::Offset,
+        stride: buffer::Stride,
+        flags: query::ResultFlags,
+    ) {
+        if buffer.is_coherent {
+            self.defer_coherent_invalidate(buffer);
+        }
+
+        let range = queries.start as usize..queries.end as usize;
+        self.query_copies.push(QueryCopy {
+            pool: QueryPool {
+                ty: pool.ty,
+                raw: pool.raw[range.clone()].to_vec(),
+                disjoint: pool.disjoint.get(range).map_or(Vec::new(), |d| d.to_vec()),
+            },
+            buffer: buffer.internal.raw,
+            offset,
+            stride,
+            flags,
+        });
+    }
+
+    unsafe fn write_timestamp(&mut self, _: pso::PipelineStage, query: query::Query<Backend>) {
+        // Timestamps are only valid within a disjoint query, which reports
+        // whether the counter was interrupted. Timestamp queries only have an end.
+        let disjoint = query.pool.disjoint_async(query.id);
+        self.context.Begin(disjoint);
+        self.context.End(query.pool.as_async(query.id));
+        self.context.End(disjoint);
+    }
+
+    unsafe fn begin_conditional_rendering(
+        &mut self,
         _buffer: &Buffer,
         _offset: buffer::Offset,
-        _stride: buffer::Stride,
-        _flags: query::ResultFlags,
+        _inverted: bool,
     ) {
-        unimplemented!()
+        // D3D11 predicates can't read a buffer, and the feature isn't exposed.
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
     }
 
-    unsafe fn write_timestamp(&mut self, _: pso::PipelineStage, _query: query::Query<Backend>) {
-        unimplemented!()
+    unsafe fn end_conditional_rendering(&mut self) {
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
     }
 
     unsafe fn push_graphics_constants(
@@ -3289,6 +3487,69 @@ enum SyncRange {
     Partial(Range<u64>),
 }
 
+/// Copy of query results into a buffer, executed after the command list.
+struct QueryCopy {
+    /// Only holds the copied queries.
+    pool: QueryPool,
+    buffer: *mut d3d11::ID3D11Buffer,
+    offset: buffer::Offset,
+    stride: buffer::Stride,
+    flags: query::ResultFlags,
+}
+
+impl QueryCopy {
+    unsafe fn execute(&self, context: &ComPtr<d3d11::ID3D11DeviceContext>) {
+        let mut data = [0u8; 12 * 8];
+        for id in 0..self.pool.raw.len() as query::Id {
+            // The queries were ended by the command lists executed before, so the
+            // results are available on the GPU timeline, as they would be on Vulkan.
+            let flags = self.flags | query::ResultFlags::WAIT;
+            let result = device::read_query(&self.pool, id, flags, |raw, out, size| {
+                context.GetData(raw, out, size, 0)
+            });
+            let (values, available) = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    error!("Failed to read query {}: {:?}", id, err);
+                    continue;
+                }
+            };
+            let size =
+                device::write_query_result(&mut data, self.pool.ty, &values, available, self.flags);
+            // Unavailable values are left untouched, unless partial results are requested.
+            let skipped = if available || self.flags.contains(query::ResultFlags::PARTIAL) {
+                0
+            } else if self.flags.contains(query::ResultFlags::WITH_AVAILABILITY) {
+                // Only the trailing availability value is written.
+                size - if self.flags.contains(query::ResultFlags::BITS_64) {
+                    8
+                } else {
+                    4
+                }
+            } else {
+                continue;
+            };
+            let start = self.offset + id as buffer::Offset * self.stride as buffer::Offset;
+            let dst_box = d3d11::D3D11_BOX {
+                left: (start + skipped as buffer::Offset) as _,
+                top: 0,
+                front: 0,
+                right: (start + size as buffer::Offset) as _,
+                bottom: 1,
+                back: 1,
+            };
+            context.UpdateSubresource(
+                self.buffer as *mut _,
+                0,
+                &dst_box,
+                data[skipped..].as_ptr() as *const _,
+                0,
+                0,
+            );
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MemoryFlush {
     host_memory: *const u8,
@@ -4464,18 +4725,56 @@ impl pso::DescriptorPool<Backend> for DescriptorPool {
     }
 }
 
-#[derive(Debug)]
 pub struct RawFence {
     mutex: Mutex<bool>,
     condvar: Condvar,
+    /// Event query issued after each submission signalling the fence.
+    query: ComPtr<d3d11::ID3D11Query>,
+    /// Whether the query was issued and the GPU isn't done with it yet.
+    pending: Mutex<bool>,
+}
+
+impl fmt::Debug for RawFence {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("RawFence")
+            .field("signaled", &*self.mutex.lock())
+            .finish()
+    }
 }
 
+unsafe impl Send for RawFence {}
+unsafe impl Sync for RawFence {}
+
 pub type Fence = Arc<RawFence>;
 
 #[derive(Debug)]
 pub struct Semaphore;
-#[derive(Debug)]
-pub struct QueryPool;
+#[derive(Clone)]
+pub struct QueryPool {
+    ty: query::Type,
+    raw: Vec<ComPtr<d3d11::ID3D11Query>>,
+    /// Disjoint queries bracketing each timestamp query, empty for other query types.
+    disjoint: Vec<ComPtr<d3d11::ID3D11Query>>,
+}
+
+impl fmt::Debug for QueryPool {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("QueryPool")
+    }
+}
+
+unsafe impl Send for QueryPool {}
+unsafe impl Sync for QueryPool {}
+
+impl QueryPool {
+    fn as_async(&self, id: query::Id) -> *mut d3d11::ID3D11Asynchronous {
+        self.raw[id as usize].as_raw() as *mut _
+    }
+
+    fn disjoint_async(&self, id: query::Id) -> *mut d3d11::ID3D11Asynchronous {
+        self.disjoint[id as usize].as_raw() as *mut _
+    }
+}
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Backend {}
@@ -8,7 +8,7 @@ use arrayvec::ArrayVec;
 use smallvec::SmallVec;
 use winapi::{
     ctypes,
-    shared::{dxgiformat, minwindef, winerror},
+    shared::{dxgiformat, dxgitype, minwindef, winerror},
     um::{d3d12, d3dcommon},
     Interface,
 };
@@ -26,6 +26,9 @@ use crate::{
 // Limited by D3D12.
 const ROOT_SIGNATURE_SIZE: usize = 64;
 
+// Minimal size of the upload heap chunks backing `update_buffer`.
+const UPLOAD_CHUNK_SIZE: u64 = 1 << 16;
+
 const NULL_VERTEX_BUFFER_VIEW: d3d12::D3D12_VERTEX_BUFFER_VIEW = d3d12::D3D12_VERTEX_BUFFER_VIEW {
     BufferLocation: 0,
     SizeInBytes: 0,
@@ -461,6 +464,8 @@ pub struct CommandBuffer {
     temporary_gpu_heaps: Vec<native::DescriptorHeap>,
     /// Resources that need to be alive till the end of the GPU execution.
     retained_resources: Vec<native::Resource>,
+    /// Staging memory for buffer updates.
+    upload_ring: UploadRing,
 
     /// Temporary wide string for the marker.
     temp_marker: Vec<u16>,
@@ -471,6 +476,113 @@ pub struct CommandBuffer {
     pub(crate) raw_name: Vec<u16>,
 }
 
+/// Mapped upload heap buffer.
+struct UploadChunk {
+    resource: native::Resource,
+    ptr: *mut u8,
+    size: u64,
+}
+
+/// Staging memory for `update_buffer`.
+///
+/// Chunks are kept when the command buffer is reset or begins recording again,
+/// at which point the GPU is done reading them, and get refilled by the next
+/// recording. They are only released when the command buffer is freed or
+/// reset with `release_resources`.
+#[derive(Default)]
+struct UploadRing {
+    chunks: Vec<UploadChunk>,
+    /// Index of the chunk currently being filled.
+    current: usize,
+    /// Offset of the first free byte in the current chunk.
+    offset: u64,
+}
+
+impl UploadRing {
+    /// Reserve `size` bytes, returning the chunk resource, the offset into it
+    /// and the mapped pointer to write the data to.
+    unsafe fn allocate(
+        &mut self,
+        device: native::Device,
+        size: u64,
+    ) -> Result<(native::Resource, u64, *mut u8), hal::device::OutOfMemory> {
+        while let Some(chunk) = self.chunks.get(self.current) {
+            if self.offset + size <= chunk.size {
+                let offset = self.offset;
+                self.offset += size;
+                return Ok((chunk.resource, offset, chunk.ptr.add(offset as usize)));
+            }
+            self.current += 1;
+            self.offset = 0;
+        }
+
+        let chunk_size = cmp::max(size, UPLOAD_CHUNK_SIZE);
+        let properties = d3d12::D3D12_HEAP_PROPERTIES {
+            Type: d3d12::D3D12_HEAP_TYPE_UPLOAD,
+            CPUPageProperty: d3d12::D3D12_CPU_PAGE_PROPERTY_UNKNOWN,
+            MemoryPoolPreference: d3d12::D3D12_MEMORY_POOL_UNKNOWN,
+            CreationNodeMask: 0,
+            VisibleNodeMask: 0,
+        };
+        let desc = d3d12::D3D12_RESOURCE_DESC {
+            Dimension: d3d12::D3D12_RESOURCE_DIMENSION_BUFFER,
+            Alignment: 0,
+            Width: chunk_size,
+            Height: 1,
+            DepthOrArraySize: 1,
+            MipLevels: 1,
+            Format: dxgiformat::DXGI_FORMAT_UNKNOWN,
+            SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Layout: d3d12::D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
+            Flags: d3d12::D3D12_RESOURCE_FLAG_NONE,
+        };
+        let mut resource = native::Resource::null();
+        let hr = device.CreateCommittedResource(
+            &properties,
+            d3d12::D3D12_HEAP_FLAG_NONE,
+            &desc,
+            d3d12::D3D12_RESOURCE_STATE_GENERIC_READ,
+            ptr::null(),
+            &d3d12::ID3D12Resource::uuidof(),
+            resource.mut_void(),
+        );
+        if !winerror::SUCCEEDED(hr) {
+            return Err(hal::device::OutOfMemory::Device);
+        }
+        let mut ptr = ptr::null_mut();
+        let hr = resource.Map(0, &d3d12::D3D12_RANGE { Begin: 0, End: 0 }, &mut ptr);
+        if !winerror::SUCCEEDED(hr) {
+            resource.destroy();
+            return Err(hal::device::OutOfMemory::Host);
+        }
+
+        self.current = self.chunks.len();
+        self.offset = size;
+        self.chunks.push(UploadChunk {
+            resource,
+            ptr: ptr as *mut u8,
+            size: chunk_size,
+        });
+        Ok((resource, 0, ptr as *mut u8))
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        self.offset = 0;
+    }
+
+    unsafe fn destroy(&mut self) {
+        for chunk in self.chunks.drain(..) {
+            chunk.resource.Unmap(0, ptr::null());
+            chunk.resource.destroy();
+        }
+        self.reset();
+    }
+}
+
 impl fmt::Debug for CommandBuffer {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.write_str("CommandBuffer")
@@ -514,6 +626,7 @@ impl CommandBuffer {
             rtv_pools: Vec::new(),
             temporary_gpu_heaps: Vec::new(),
             retained_resources: Vec::new(),
+            upload_ring: UploadRing::default(),
             temp_marker: Vec::new(),
             barriers: Vec::new(),
             raw_name: Vec::new(),
@@ -521,7 +634,7 @@ impl CommandBuffer {
     }
 
     pub(crate) unsafe fn destroy(
-        self,
+        mut self,
     ) -> Option<(CommandAllocatorIndex, Option<native::GraphicsCommandList>)> {
         let list = match self.phase {
             Phase::Initial => None,
@@ -540,9 +653,24 @@ impl CommandBuffer {
         for resource in &self.retained_resources {
             resource.destroy();
         }
+        self.upload_ring.destroy();
         self.allocator_index.map(|index| (index, list))
     }
 
+    // Bundles can't record render passes, clears, copies, resolves, barriers,
+    // queries or predication. Logs and returns `true` if `command` has to be skipped.
+    fn skip_in_bundle(&self, command: &str) -> bool {
+        let is_bundle = self.pool_shared.is_bundle();
+        if is_bundle {
+            error!("{} can't be recorded into bundles", command);
+        }
+        is_bundle
+    }
+
+    pub(crate) fn pool_shared(&self) -> &Arc<PoolShared> {
+        &self.pool_shared
+    }
+
     pub(crate) unsafe fn as_raw_list(&self) -> *mut d3d12::ID3D12CommandList {
         match self.phase {
             Phase::Executable => (),
@@ -1227,10 +1355,12 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         flags: com::CommandBufferFlags,
         _info: com::CommandBufferInheritanceInfo<Backend>,
     ) {
-        // TODO: Implement flags and secondary command buffers (bundles).
+        // TODO: Implement flags.
         // Note: we need to be ready for a situation where the whole
         // command pool was reset.
-        self.reset(true);
+        // The previous submission is done at this point, so the upload chunks
+        // are kept for reuse.
+        self.reset(false);
         self.phase = Phase::Recording;
         self.begin_flags = flags;
         let (allocator_index, list) = self.pool_shared.acquire();
@@ -1253,7 +1383,7 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
             .release_allocator(self.allocator_index.unwrap());
     }
 
-    unsafe fn reset(&mut self, _release_resources: bool) {
+    unsafe fn reset(&mut self, release_resources: bool) {
         if self.phase == Phase::Recording {
             self.raw.close();
             self.pool_shared
@@ -1290,6 +1420,11 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         for resource in self.retained_resources.drain(..) {
             resource.destroy();
         }
+        if release_resources {
+            self.upload_ring.destroy();
+        } else {
+            self.upload_ring.reset();
+        }
     }
 
     unsafe fn begin_render_pass<'a, T>(
@@ -1302,6 +1437,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::RenderAttachmentInfo<'a, Backend>>,
     {
+        if self.skip_in_bundle("begin_render_pass") {
+            return;
+        }
         // Make sure that no subpass works with Present as intermediate layout.
         // This wouldn't make much sense, and proceeding with this constraint
         // allows the state transitions generated from subpass dependencies
@@ -1373,6 +1511,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn next_subpass(&mut self, _contents: com::SubpassContents) {
+        if self.skip_in_bundle("next_subpass") {
+            return;
+        }
         self.insert_subpass_barriers(BarrierPoint::Post);
         self.resolve_attachments();
 
@@ -1382,6 +1523,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn end_render_pass(&mut self) {
+        if self.skip_in_bundle("end_render_pass") {
+            return;
+        }
         self.insert_subpass_barriers(BarrierPoint::Post);
         self.resolve_attachments();
 
@@ -1419,6 +1563,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = memory::Barrier<'a, Backend>>,
     {
+        if self.skip_in_bundle("pipeline_barrier") {
+            return;
+        }
         self.barriers.clear();
 
         // transition barriers
@@ -1548,6 +1695,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = image::SubresourceRange>,
     {
+        if self.skip_in_bundle("clear_image") {
+            return;
+        }
         let image = image.expect_bound();
         let base_state = conv::map_image_resource_state(image::Access::TRANSFER_WRITE, layout);
 
@@ -1596,6 +1746,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         T: Iterator<Item = com::AttachmentClear>,
         U: Iterator<Item = pso::ClearRect>,
     {
+        if self.skip_in_bundle("clear_attachments") {
+            return;
+        }
         let pass_cache = match self.pass_cache {
             Some(ref cache) => cache,
             None => panic!("`clear_attachments` can only be called inside a renderpass"),
@@ -1690,6 +1843,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::ImageResolve>,
     {
+        if self.skip_in_bundle("resolve_image") {
+            return;
+        }
         let src = src.expect_bound();
         let dst = dst.expect_bound();
         assert_eq!(src.descriptor.Format, dst.descriptor.Format);
@@ -1751,6 +1907,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::ImageBlit>,
     {
+        if self.skip_in_bundle("blit_image") {
+            return;
+        }
         let device = self.shared.service_pipes.device.clone();
         let src = src.expect_bound();
         let dst = dst.expect_bound();
@@ -1966,6 +2125,10 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         }
     }
 
+    unsafe fn generate_mipmaps(&mut self, _image: &r::Image) {
+        error!("Mipmap generation is not supported");
+    }
+
     unsafe fn bind_index_buffer(
         &mut self,
         buffer: &r::Buffer,
@@ -2023,8 +2186,13 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
             }
         }
 
-        self.raw
-            .RSSetViewports(self.viewport_cache.len() as _, self.viewport_cache.as_ptr());
+        // Bundles inherit the viewports of the executing command list.
+        if self.pool_shared.is_bundle() {
+            error!("Setting viewports in secondary command buffers is not supported");
+        } else {
+            self.raw
+                .RSSetViewports(self.viewport_cache.len() as _, self.viewport_cache.as_ptr());
+        }
     }
 
     unsafe fn set_scissors<T>(&mut self, first_scissor: u32, scissors: T)
@@ -2040,8 +2208,13 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
             }
         }
 
-        self.raw
-            .RSSetScissorRects(self.scissor_cache.len() as _, self.scissor_cache.as_ptr())
+        // Bundles inherit the scissor rects of the executing command list.
+        if self.pool_shared.is_bundle() {
+            error!("Setting scissor rects in secondary command buffers is not supported");
+        } else {
+            self.raw
+                .RSSetScissorRects(self.scissor_cache.len() as _, self.scissor_cache.as_ptr())
+        }
     }
 
     unsafe fn set_blend_constants(&mut self, color: pso::ColorValue) {
@@ -2205,6 +2378,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn fill_buffer(&mut self, buffer: &r::Buffer, range: buffer::SubRange, data: u32) {
+        if self.skip_in_bundle("fill_buffer") {
+            return;
+        }
         let buffer = buffer.expect_bound();
         let bytes_per_unit = 4;
         let start = range.offset as i32;
@@ -2273,14 +2449,36 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         self.raw.ResourceBarrier(1, &post_barrier);
     }
 
-    unsafe fn update_buffer(&mut self, _buffer: &r::Buffer, _offset: buffer::Offset, _data: &[u8]) {
-        unimplemented!()
+    unsafe fn update_buffer(&mut self, buffer: &r::Buffer, offset: buffer::Offset, data: &[u8]) {
+        if self.skip_in_bundle("update_buffer") {
+            return;
+        }
+        let buffer = buffer.expect_bound();
+        let device = self.shared.service_pipes.device.clone();
+        let (src, src_offset, ptr) = match self.upload_ring.allocate(device, data.len() as u64) {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                error!("Unable to allocate upload memory: {:?}", e);
+                return;
+            }
+        };
+        ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+        self.raw.CopyBufferRegion(
+            buffer.resource.as_mut_ptr(),
+            offset,
+            src.as_mut_ptr(),
+            src_offset,
+            data.len() as _,
+        );
     }
 
     unsafe fn copy_buffer<T>(&mut self, src: &r::Buffer, dst: &r::Buffer, regions: T)
     where
         T: Iterator<Item = com::BufferCopy>,
     {
+        if self.skip_in_bundle("copy_buffer") {
+            return;
+        }
         let src = src.expect_bound();
         let dst = dst.expect_bound();
 
@@ -2318,6 +2516,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::ImageCopy>,
     {
+        if self.skip_in_bundle("copy_image") {
+            return;
+        }
         let src = src.expect_bound();
         let dst = dst.expect_bound();
         let mut src_image = d3d12::D3D12_TEXTURE_COPY_LOCATION {
@@ -2446,6 +2647,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::BufferImageCopy>,
     {
+        if self.skip_in_bundle("copy_buffer_to_image") {
+            return;
+        }
         let buffer = buffer.expect_bound();
         let image = image.expect_bound();
         assert!(self.copies.is_empty());
@@ -2510,6 +2714,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     ) where
         T: Iterator<Item = com::BufferImageCopy>,
     {
+        if self.skip_in_bundle("copy_image_to_buffer") {
+            return;
+        }
         let image = image.expect_bound();
         let buffer = buffer.expect_bound();
         assert!(self.copies.is_empty());
@@ -2720,6 +2927,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn begin_query(&mut self, query: query::Query<Backend>, flags: query::ControlFlags) {
+        if self.skip_in_bundle("begin_query") {
+            return;
+        }
         let query_ty = match query.pool.ty {
             query::Type::Occlusion => {
                 if flags.contains(query::ControlFlags::PRECISE) {
@@ -2744,6 +2954,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn end_query(&mut self, query: query::Query<Backend>) {
+        if self.skip_in_bundle("end_query") {
+            return;
+        }
         let id = query.id;
         let query_ty = match query.pool.ty {
             query::Type::Occlusion if self.occlusion_query == Some(OcclusionQuery::Precise(id)) => {
@@ -2786,6 +2999,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn write_timestamp(&mut self, _: pso::PipelineStage, query: query::Query<Backend>) {
+        if self.skip_in_bundle("write_timestamp") {
+            return;
+        }
         self.raw.EndQuery(
             query.pool.raw.as_mut_ptr(),
             d3d12::D3D12_QUERY_TYPE_TIMESTAMP,
@@ -2793,6 +3009,34 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         );
     }
 
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        buffer: &r::Buffer,
+        offset: buffer::Offset,
+        inverted: bool,
+    ) {
+        if self.skip_in_bundle("begin_conditional_rendering") {
+            return;
+        }
+        let buffer = buffer.expect_bound();
+        // Commands are skipped while the predication is enabled.
+        let op = if inverted {
+            d3d12::D3D12_PREDICATION_OP_NOT_EQUAL_ZERO
+        } else {
+            d3d12::D3D12_PREDICATION_OP_EQUAL_ZERO
+        };
+        self.raw
+            .SetPredication(buffer.resource.as_mut_ptr(), offset, op);
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        if self.skip_in_bundle("end_conditional_rendering") {
+            return;
+        }
+        self.raw
+            .SetPredication(ptr::null_mut(), 0, d3d12::D3D12_PREDICATION_OP_EQUAL_ZERO);
+    }
+
     unsafe fn push_graphics_constants(
         &mut self,
         _layout: &r::PipelineLayout,
@@ -2822,9 +3066,24 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     where
         T: Iterator<Item = &'a CommandBuffer>,
     {
-        for _cmd_buf in cmd_buffers {
-            error!("TODO: execute_commands");
+        if self.skip_in_bundle("execute_commands") {
+            return;
         }
+        for cmd_buf in cmd_buffers {
+            if !cmd_buf.pool_shared.is_bundle() {
+                error!("Only secondary command buffers can be executed");
+                continue;
+            }
+            self.raw.ExecuteBundle(cmd_buf.raw.as_mut_ptr());
+        }
+
+        // State set inside the bundles leaks into this command list,
+        // so everything has to be re-applied before the next draw.
+        self.gr_pipeline.pipeline = None;
+        self.gr_pipeline.user_data.dirty_all();
+        self.comp_pipeline.pipeline = None;
+        self.comp_pipeline.user_data.dirty_all();
+        self.primitive_topology = d3dcommon::D3D_PRIMITIVE_TOPOLOGY_UNDEFINED;
     }
 
     unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
@@ -7,6 +7,7 @@ use winapi::{
     shared::{
         basetsd::UINT8,
         dxgiformat::*,
+        dxgitype,
         minwindef::{FALSE, INT, TRUE, UINT},
     },
     um::{d3d12::*, d3dcommon::*},
@@ -16,7 +17,7 @@ use auxil::ShaderStage;
 use hal::{
     buffer,
     format::{Format, ImageFeature, SurfaceType, Swizzle},
-    image, pso,
+    image, pso, window,
 };
 
 use native::ShaderVisibility;
@@ -151,6 +152,19 @@ pub fn map_format_nosrgb(format: Format) -> Option<DXGI_FORMAT> {
     }
 }
 
+/// Returns `None` unless exactly one color space is specified.
+pub fn map_color_space(color_space: window::ColorSpace) -> Option<dxgitype::DXGI_COLOR_SPACE_TYPE> {
+    if color_space == window::ColorSpace::SRGB_NONLINEAR {
+        Some(dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G22_NONE_P709)
+    } else if color_space == window::ColorSpace::EXTENDED_SRGB_LINEAR {
+        Some(dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709)
+    } else if color_space == window::ColorSpace::HDR10_ST2084 {
+        Some(dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020)
+    } else {
+        None
+    }
+}
+
 pub fn map_swizzle(swizzle: Swizzle) -> UINT {
     use hal::format::Component::*;
 
@@ -534,6 +548,9 @@ pub fn map_buffer_resource_state(access: buffer::Access) -> D3D12_RESOURCE_STATE
     if access.contains(Access::INDIRECT_COMMAND_READ) {
         state |= D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT;
     }
+    if access.contains(Access::CONDITIONAL_RENDERING_READ) {
+        state |= D3D12_RESOURCE_STATE_PREDICATION;
+    }
     if access.contains(Access::SHADER_READ) {
         // SHADER_READ only allows SRV access
         state |= D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE
@@ -2027,8 +2027,8 @@ impl d::Device<B> for Device {
 
         let vs = build_shader(ShaderStage::Vertex, vs)?;
         let gs = build_shader(ShaderStage::Geometry, gs)?;
-        let hs = build_shader(ShaderStage::Domain, hs)?;
-        let ds = build_shader(ShaderStage::Hull, ds)?;
+        let hs = build_shader(ShaderStage::Hull, hs)?;
+        let ds = build_shader(ShaderStage::Domain, ds)?;
         let ps = build_shader(ShaderStage::Fragment, desc.fragment.as_ref())?;
 
         // Rebind vertex buffers, see native.rs for more details.
@@ -3717,7 +3717,7 @@ impl d::Device<B> for Device {
         unimplemented!()
     }
 
-    fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), d::WaitError> {
         for queue in &self.queues {
             queue.wait_idle_impl()?;
         }
@@ -44,7 +44,7 @@ use parking_lot::{Mutex, RwLock};
 use smallvec::SmallVec;
 use winapi::{
     shared::{dxgi, dxgi1_2, dxgi1_4, dxgi1_6, minwindef::TRUE, winerror},
-    um::{d3d12, d3d12sdklayers, handleapi, synchapi, winbase},
+    um::{d3d12, d3d12sdklayers, handleapi, synchapi, winbase, winnt},
     Interface,
 };
 
@@ -237,6 +237,38 @@ impl fmt::Debug for PhysicalDevice {
 unsafe impl Send for PhysicalDevice {}
 unsafe impl Sync for PhysicalDevice {}
 
+impl PhysicalDevice {
+    /// Register `event` to be signaled whenever the budget reported by `memory_budget`
+    /// changes, so it doesn't need to be polled.
+    ///
+    /// Returns the cookie for `unregister_memory_budget_event`, or `None` if the
+    /// adapter doesn't support budget notifications.
+    pub unsafe fn register_memory_budget_event(&self, event: winnt::HANDLE) -> Option<u32> {
+        let (adapter3, hr) = self.adapter.cast::<dxgi1_4::IDXGIAdapter3>();
+        if !winerror::SUCCEEDED(hr) {
+            return None;
+        }
+        let mut cookie = 0;
+        let hr = adapter3.RegisterVideoMemoryBudgetChangeNotificationEvent(event, &mut cookie);
+        adapter3.destroy();
+        if winerror::SUCCEEDED(hr) {
+            Some(cookie)
+        } else {
+            error!("error on memory budget registration 0x{:x}", hr);
+            None
+        }
+    }
+
+    /// Stop signaling the event registered with `register_memory_budget_event`.
+    pub unsafe fn unregister_memory_budget_event(&self, cookie: u32) {
+        let (adapter3, hr) = self.adapter.cast::<dxgi1_4::IDXGIAdapter3>();
+        if winerror::SUCCEEDED(hr) {
+            adapter3.UnregisterVideoMemoryBudgetChangeNotification(cookie);
+            adapter3.destroy();
+        }
+    }
+}
+
 impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
     unsafe fn open(
         &self,
@@ -474,6 +506,45 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         self.properties
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        let (adapter3, hr) = unsafe { self.adapter.cast::<dxgi1_4::IDXGIAdapter3>() };
+        if !winerror::SUCCEEDED(hr) {
+            return None;
+        }
+
+        // Heaps are laid out as in `enumerate_adapters`: local memory first,
+        // followed by non-local memory on NUMA architectures.
+        let segments = [
+            dxgi1_4::DXGI_MEMORY_SEGMENT_GROUP_LOCAL,
+            dxgi1_4::DXGI_MEMORY_SEGMENT_GROUP_NON_LOCAL,
+        ];
+        let mut budgets = Vec::with_capacity(self.memory_properties.memory_heaps.len());
+        for &segment in segments
+            .iter()
+            .take(self.memory_properties.memory_heaps.len())
+        {
+            let mut mem_info: dxgi1_4::DXGI_QUERY_VIDEO_MEMORY_INFO = unsafe { mem::zeroed() };
+            let hr = unsafe { adapter3.QueryVideoMemoryInfo(0, segment, &mut mem_info) };
+            if !winerror::SUCCEEDED(hr) {
+                error!("error on video memory query 0x{:x}", hr);
+                break;
+            }
+            budgets.push(adapter::MemoryHeapBudget {
+                budget: mem_info.Budget,
+                usage: mem_info.CurrentUsage,
+            });
+        }
+        unsafe {
+            adapter3.destroy();
+        }
+
+        if budgets.len() == self.memory_properties.memory_heaps.len() {
+            Some(budgets)
+        } else {
+            None
+        }
+    }
+
     unsafe fn enumerate_displays(&self) -> Vec<display::Display<crate::Backend>> {
         unimplemented!();
     }
@@ -523,7 +594,7 @@ impl Queue {
         self.raw.destroy();
     }
 
-    fn wait_idle_impl(&self) -> Result<(), hal::device::OutOfMemory> {
+    fn wait_idle_impl(&self) -> Result<(), hal::device::WaitError> {
         self.raw.signal(self.idle_fence, 1);
         assert_eq!(
             winerror::S_OK,
@@ -532,10 +603,41 @@ impl Queue {
 
         unsafe {
             synchapi::WaitForSingleObject(self.idle_event.0, winbase::INFINITE);
+            // Fences of a removed device complete with the maximum value.
+            if self.idle_fence.GetCompletedValue() == u64::MAX {
+                return Err(hal::device::DeviceLost.into());
+            }
         }
 
         Ok(())
     }
+
+    /// Semaphores are binary: a signal sets the fence to 1, and a wait
+    /// resets it back to 0 once satisfied, so it can be signaled again.
+    unsafe fn wait_semaphores<'a, I>(&self, semaphores: I)
+    where
+        I: Iterator<Item = &'a resource::Semaphore>,
+    {
+        for semaphore in semaphores {
+            assert_eq!(winerror::S_OK, self.raw.Wait(semaphore.raw.as_mut_ptr(), 1));
+            assert_eq!(
+                winerror::S_OK,
+                self.raw.Signal(semaphore.raw.as_mut_ptr(), 0)
+            );
+        }
+    }
+
+    unsafe fn signal_semaphores<'a, I>(&self, semaphores: I)
+    where
+        I: Iterator<Item = &'a resource::Semaphore>,
+    {
+        for semaphore in semaphores {
+            assert_eq!(
+                winerror::S_OK,
+                self.raw.Signal(semaphore.raw.as_mut_ptr(), 1)
+            );
+        }
+    }
 }
 
 unsafe impl Send for Queue {}
@@ -545,8 +647,8 @@ impl q::Queue<Backend> for Queue {
     unsafe fn submit<'a, Ic, Iw, Is>(
         &mut self,
         command_buffers: Ic,
-        _wait_semaphores: Iw,
-        _signal_semaphores: Is,
+        wait_semaphores: Iw,
+        signal_semaphores: Is,
         fence: Option<&mut resource::Fence>,
     ) where
         Ic: Iterator<Item = &'a command::CommandBuffer>,
@@ -558,12 +660,13 @@ impl q::Queue<Backend> for Queue {
         self.idle_fence.signal(0);
         synchapi::ResetEvent(self.idle_event.0);
 
-        // TODO: semaphores
+        self.wait_semaphores(wait_semaphores.map(|(semaphore, _)| semaphore));
         let lists = command_buffers
             .map(|cmd_buf| cmd_buf.as_raw_list())
             .collect::<SmallVec<[_; 4]>>();
         self.raw
             .ExecuteCommandLists(lists.len() as _, lists.as_ptr());
+        self.signal_semaphores(signal_semaphores);
 
         if let Some(fence) = fence {
             assert_eq!(winerror::S_OK, self.raw.Signal(fence.raw.as_mut_ptr(), 1));
@@ -572,8 +675,8 @@ impl q::Queue<Backend> for Queue {
 
     unsafe fn bind_sparse<'a, Iw, Is, Ibi, Ib, Iii, Io, Ii>(
         &mut self,
-        _wait_semaphores: Iw,
-        _signal_semaphores: Is,
+        wait_semaphores: Iw,
+        signal_semaphores: Is,
         _buffer_memory_binds: Ib,
         _image_opaque_memory_binds: Io,
         image_memory_binds: Ii,
@@ -593,7 +696,7 @@ impl q::Queue<Backend> for Queue {
         self.idle_fence.signal(0);
         synchapi::ResetEvent(self.idle_event.0);
 
-        // TODO: semaphores
+        self.wait_semaphores(wait_semaphores);
 
         for (image, binds) in image_memory_binds {
             let image = image.borrow_mut();
@@ -714,6 +817,7 @@ impl q::Queue<Backend> for Queue {
         }
         // TODO sparse buffers and opaque images iterated here
 
+        self.signal_semaphores(signal_semaphores);
         if let Some(fence) = fence {
             assert_eq!(winerror::S_OK, self.raw.Signal(fence.raw.as_mut_ptr(), 1));
         }
@@ -723,12 +827,16 @@ impl q::Queue<Backend> for Queue {
         &mut self,
         surface: &mut window::Surface,
         image: window::SwapchainImage,
-        _wait_semaphore: Option<&mut resource::Semaphore>,
+        wait_semaphore: Option<&mut resource::Semaphore>,
     ) -> Result<Option<hal::window::Suboptimal>, hal::window::PresentError> {
+        // The swapchain presents from this queue, so the wait is ordered before it.
+        if let Some(semaphore) = wait_semaphore {
+            self.wait_semaphores(std::iter::once(&*semaphore));
+        }
         surface.present(image).map(|()| None)
     }
 
-    fn wait_idle(&mut self) -> Result<(), hal::device::OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), hal::device::WaitError> {
         self.wait_idle_impl()
     }
 
@@ -1407,7 +1515,9 @@ impl hal::Instance<Backend> for Instance {
                     Features::UNIFORM_BUFFER_DESCRIPTOR_INDEXING |
                     Features::UNSIZED_DESCRIPTOR_ARRAY |
                     Features::DRAW_INDIRECT_COUNT |
+                    Features::CONDITIONAL_RENDERING |
                     Features::INDEPENDENT_BLENDING |
+                    Features::DUAL_SRC_BLENDING |
                     Features::SAMPLE_RATE_SHADING |
                     Features::FRAGMENT_STORES_AND_ATOMICS |
                     tiled_resource_features |
@@ -1459,7 +1569,7 @@ impl hal::Instance<Backend> for Instance {
                         max_image_cube_size: d3d12::D3D12_REQ_TEXTURECUBE_DIMENSION as _,
                         max_image_array_layers: d3d12::D3D12_REQ_TEXTURE2D_ARRAY_AXIS_DIMENSION as _,
                         max_texel_elements: 0,
-                        max_patch_size: 0,
+                        max_patch_size: d3d12::D3D12_IA_PATCH_MAX_CONTROL_POINT_COUNT as _,
                         max_viewports: d3d12::D3D12_VIEWPORT_AND_SCISSORRECT_OBJECT_COUNT_PER_PIPELINE as _,
                         max_viewport_dimensions: [d3d12::D3D12_VIEWPORT_BOUNDS_MAX as _; 2],
                         max_framebuffer_extent: hal::image::Extent {
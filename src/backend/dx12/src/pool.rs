@@ -114,6 +114,13 @@ impl PoolShared {
         (allocator_index, list)
     }
 
+    pub fn is_bundle(&self) -> bool {
+        match self.list_type {
+            native::CmdListType::Bundle => true,
+            _ => false,
+        }
+    }
+
     pub fn release_allocator(&self, allocator_index: CommandAllocatorIndex) {
         self.manager.lock().release_allocator(allocator_index);
     }
@@ -131,6 +138,9 @@ impl PoolShared {
 pub struct CommandPool {
     shared: Arc<Shared>,
     pool_shared: Arc<PoolShared>,
+    // Secondary command buffers are recorded as bundles,
+    // which can only be executed on direct command lists.
+    bundle_shared: Option<Arc<PoolShared>>,
 }
 
 unsafe impl Send for CommandPool {}
@@ -148,9 +158,18 @@ impl CommandPool {
             list_type,
             manager: Mutex::default(),
         });
+        let bundle_shared = match list_type {
+            native::CmdListType::Direct => Some(Arc::new(PoolShared {
+                device,
+                list_type: native::CmdListType::Bundle,
+                manager: Mutex::default(),
+            })),
+            _ => None,
+        };
         CommandPool {
             shared: Arc::clone(shared),
             pool_shared,
+            bundle_shared,
         }
     }
 }
@@ -164,18 +183,29 @@ impl pool::CommandPool<Backend> for CommandPool {
     }
 
     unsafe fn allocate_one(&mut self, level: command::Level) -> CommandBuffer {
-        // TODO: Implement secondary buffers
-        assert_eq!(level, command::Level::Primary);
-        CommandBuffer::new(&self.shared, &self.pool_shared)
+        match level {
+            command::Level::Primary => CommandBuffer::new(&self.shared, &self.pool_shared),
+            // Bundles inherit the viewports, scissor rects and render targets
+            // of the command list executing them, so `set_viewports` and
+            // `set_scissors` are only cached and never recorded into them.
+            command::Level::Secondary => {
+                let bundle_shared = self
+                    .bundle_shared
+                    .as_ref()
+                    .expect("Secondary command buffers require a graphics queue family");
+                CommandBuffer::new(&self.shared, bundle_shared)
+            }
+        }
     }
 
     unsafe fn free<I>(&mut self, cbufs: I)
     where
         I: Iterator<Item = CommandBuffer>,
     {
-        let mut man_guard = self.pool_shared.manager.lock();
         for cbuf in cbufs {
+            let pool_shared = Arc::clone(cbuf.pool_shared());
             if let Some((index, list)) = cbuf.destroy() {
+                let mut man_guard = pool_shared.manager.lock();
                 man_guard.release_allocator(index);
                 if let Some(list) = list {
                     man_guard.release_list(list, index);
@@ -29,6 +29,7 @@ struct Presentation {
     format: f::Format,
     size: w::Extent2D,
     mode: w::PresentMode,
+    color_space: w::ColorSpace,
 }
 
 pub struct Surface {
@@ -68,8 +69,19 @@ impl Surface {
             _ => (1, 0), // Surface was created with an unsupported present mode, fall back to FIFO
         };
 
-        sc.inner.Present(interval, flags);
-        Ok(())
+        match sc.inner.Present(interval, flags) {
+            winerror::DXGI_ERROR_DEVICE_REMOVED
+            | winerror::DXGI_ERROR_DEVICE_RESET
+            | winerror::DXGI_ERROR_DEVICE_HUNG
+            | winerror::DXGI_ERROR_DRIVER_INTERNAL_ERROR => Err(hal::device::DeviceLost.into()),
+            winerror::E_OUTOFMEMORY => Err(hal::device::OutOfMemory::Host.into()),
+            hr if !winerror::SUCCEEDED(hr) => {
+                // Anything else leaves the swapchain unusable, so have it re-created.
+                error!("Present failed: 0x{:x}", hr);
+                Err(w::OutOfDate.into())
+            }
+            _ => Ok(()),
+        }
     }
 }
 
@@ -116,9 +128,33 @@ impl w::Surface<Backend> for Surface {
             present_modes |= w::PresentMode::IMMEDIATE;
         }
 
+        // DXGI can only check color space support against an existing swapchain,
+        // so the HDR color spaces are reported once one has been configured.
+        let mut color_spaces = w::ColorSpace::SRGB_NONLINEAR;
+        if let Some(ref present) = self.presentation {
+            for &color_space in &[
+                w::ColorSpace::EXTENDED_SRGB_LINEAR,
+                w::ColorSpace::HDR10_ST2084,
+            ] {
+                let mut support = 0;
+                let hr = unsafe {
+                    present.swapchain.inner.CheckColorSpaceSupport(
+                        conv::map_color_space(color_space).unwrap(),
+                        &mut support,
+                    )
+                };
+                if winerror::SUCCEEDED(hr)
+                    && support & dxgi1_4::DXGI_SWAP_CHAIN_COLOR_SPACE_SUPPORT_FLAG_PRESENT != 0
+                {
+                    color_spaces |= color_space;
+                }
+            }
+        }
+
         w::SurfaceCapabilities {
             present_modes,
             composite_alpha_modes: w::CompositeAlphaMode::OPAQUE, //TODO
+            color_spaces,
             image_count: 2..=16, // we currently use a flip effect which supports 2..=16 buffers
             current_extent,
             extents: w::Extent2D {
@@ -173,10 +209,15 @@ impl w::PresentationSurface<Backend> for Surface {
         config: w::SwapchainConfig,
     ) -> Result<(), w::SwapchainError> {
         assert!(i::Usage::COLOR_ATTACHMENT.contains(config.image_usage));
+        let color_space = conv::map_color_space(config.color_space)
+            .ok_or(w::SwapchainError::UnsupportedColorSpace)?;
 
         let swapchain = match self.presentation.take() {
             Some(present) => {
-                if present.format == config.format && present.size == config.extent {
+                if present.format == config.format
+                    && present.size == config.extent
+                    && present.color_space == config.color_space
+                {
                     self.presentation = Some(present);
                     return Ok(());
                 }
@@ -209,6 +250,17 @@ impl w::PresentationSurface<Backend> for Surface {
             }
         };
 
+        // Support depends on the swapchain format, so it can only be checked now.
+        let mut support = 0;
+        let hr = swapchain.CheckColorSpaceSupport(color_space, &mut support);
+        if !winerror::SUCCEEDED(hr)
+            || support & dxgi1_4::DXGI_SWAP_CHAIN_COLOR_SPACE_SUPPORT_FLAG_PRESENT == 0
+            || !winerror::SUCCEEDED(swapchain.SetColorSpace1(color_space))
+        {
+            swapchain.destroy();
+            return Err(w::SwapchainError::UnsupportedColorSpace);
+        }
+
         // Disable automatic Alt+Enter handling by DXGI.
         const DXGI_MWA_NO_WINDOW_CHANGES: u32 = 1;
         const DXGI_MWA_NO_ALT_ENTER: u32 = 2;
@@ -222,6 +274,7 @@ impl w::PresentationSurface<Backend> for Surface {
             format: config.format,
             size: config.extent,
             mode: config.present_mode,
+            color_space: config.color_space,
         });
         Ok(())
     }
@@ -231,7 +231,7 @@ impl queue::Queue<Backend> for Queue {
         Ok(None)
     }
 
-    fn wait_idle(&mut self) -> Result<(), device::OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), device::WaitError> {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
@@ -574,7 +574,7 @@ impl device::Device<Backend> for Device {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
-    fn wait_idle(&self) -> Result<(), device::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), device::WaitError> {
         Ok(())
     }
 
@@ -863,6 +863,10 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
+    unsafe fn generate_mipmaps(&mut self, _: &Image) {
+        unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
+    }
+
     unsafe fn bind_index_buffer(
         &mut self,
         _: &Buffer,
@@ -1109,6 +1113,14 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!("{}", NOT_SUPPORTED_MESSAGE)
     }
 
+    unsafe fn begin_conditional_rendering(&mut self, _: &Buffer, _: hal::buffer::Offset, _: bool) {
+        debug_assert!(false, "{}", NOT_SUPPORTED_MESSAGE);
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        debug_assert!(false, "{}", NOT_SUPPORTED_MESSAGE);
+    }
+
     unsafe fn push_graphics_constants(
         &mut self,
         _: &(),
@@ -1172,6 +1184,7 @@ impl window::Surface<Backend> for Surface {
             usage,
             present_modes,
             composite_alpha_modes,
+            color_spaces: window::ColorSpace::all(),
         }
     }
 
@@ -73,6 +73,24 @@ pub enum Command {
         base_vertex: hal::VertexOffset,
         instances: Range<hal::InstanceCount>,
     },
+    DrawIndirect {
+        primitive: u32,
+        buffer: n::RawBuffer,
+        offset: buffer::Offset,
+        /// Buffer and offset of the draw count, `draw_count` is the maximum then.
+        count_buffer: Option<(n::RawBuffer, buffer::Offset)>,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
+    },
+    DrawIndexedIndirect {
+        primitive: u32,
+        index_type: u32,
+        buffer: n::RawBuffer,
+        offset: buffer::Offset,
+        count_buffer: Option<(n::RawBuffer, buffer::Offset)>,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
+    },
     BindIndexBuffer(n::RawBuffer),
     //BindVertexBuffers(BufferSlice),
     BindUniform {
@@ -82,6 +100,7 @@ pub enum Command {
     BindRasterizer {
         rasterizer: pso::Rasterizer,
     },
+    BindMultisampling(Option<pso::Multisampling>),
     BindDepth(Option<pso::Comparison>),
     SetViewports {
         first_viewport: u32,
@@ -90,6 +109,8 @@ pub enum Command {
     },
     SetScissors(u32, BufferSlice),
     SetBlendColor(pso::ColorValue),
+    SetLineWidth(f32),
+    SetDepthBias(pso::DepthBias),
 
     /// Clear floating-point color drawbuffer of bound framebuffer.
     ClearBufferColorF(DrawBuffer, [f32; 4]),
@@ -109,7 +130,11 @@ pub enum Command {
         depth_stencil: Option<n::ImageView>,
     },
     SetDrawColorBuffers(ArrayVec<[u8; MAX_COLOR_ATTACHMENTS]>),
+    /// Discard the contents of the given attachments of the draw framebuffer.
+    InvalidateAttachments(ArrayVec<[u32; MAX_COLOR_ATTACHMENTS + 2]>),
+    SetFramebufferSrgb(bool),
     SetPatchSize(i32),
+    SetPrimitiveRestart(bool),
     BindProgram(<GlContext as glow::HasContext>::Program),
     SetBlend(Option<pso::BlendState>),
     SetBlendSlot(ColorSlot, Option<pso::BlendState>),
@@ -130,16 +155,35 @@ pub enum Command {
         pixel_type: n::DataType,
         data: command::BufferImageCopy,
     },
+    CopyBufferToCompressedTexture {
+        src_buffer: n::RawBuffer,
+        dst_texture: n::Texture,
+        texture_target: n::TextureTarget,
+        texture_format: n::TextureFormat,
+        /// Size of a compressed block in bytes.
+        block_size: u32,
+        /// Width and height of a compressed block in texels.
+        block_dim: (u8, u8),
+        data: command::BufferImageCopy,
+    },
     CopyBufferToRenderbuffer(n::RawBuffer, n::Renderbuffer, command::BufferImageCopy),
     CopyTextureToBuffer {
         src_texture: n::Texture,
         texture_target: n::TextureTarget,
         texture_format: n::TextureFormat,
         pixel_type: n::DataType,
+        /// Size of a texel in bytes, used to step between layers in the buffer.
+        texel_size: u32,
+        dst_buffer: n::RawBuffer,
+        data: command::BufferImageCopy,
+    },
+    CopyRenderbufferToBuffer {
+        src_renderbuffer: n::Renderbuffer,
+        texture_format: n::TextureFormat,
+        pixel_type: n::DataType,
         dst_buffer: n::RawBuffer,
         data: command::BufferImageCopy,
     },
-    CopyRenderbufferToBuffer(n::Renderbuffer, n::RawBuffer, command::BufferImageCopy),
     CopyImageToTexture(
         n::ImageType,
         n::Texture,
@@ -152,8 +196,24 @@ pub enum Command {
         dst_format: n::TextureFormat,
         data: command::ImageCopy,
     },
+    BlitImage {
+        src_image: n::ImageType,
+        dst_image: n::ImageType,
+        filter: u32,
+        data: command::ImageBlit,
+    },
+    GenerateMipmap(n::Texture, n::TextureTarget, image::Level),
     BindBufferRange(u32, u32, n::RawBuffer, i32, i32),
     BindTexture(u32, n::Texture, n::TextureTarget),
+    BindImage {
+        unit: u32,
+        texture: n::Texture,
+        level: image::Level,
+        layer: Option<image::Layer>,
+        access: u32,
+        format: n::TextureFormat,
+    },
+    SetTextureLevels(u32, n::TextureTarget, Range<image::Level>),
     BindSampler(u32, n::Sampler),
     SetTextureSamplerSettings(u32, n::TextureTarget, image::SamplerDesc),
     SetColorMask(Option<DrawBuffer>, pso::ColorMask),
@@ -161,6 +221,9 @@ pub enum Command {
     SetStencilMask(pso::StencilValue),
     SetStencilMaskSeparate(pso::Sided<pso::StencilValue>),
     MemoryBarrier(u32),
+    InsertDebugMarker(BufferSlice),
+    PushDebugGroup(BufferSlice),
+    PopDebugGroup,
 }
 
 pub type FrameBufferTarget = u32;
@@ -168,14 +231,14 @@ pub type DrawBuffer = u32;
 
 #[derive(Clone, Debug)]
 struct AttachmentInfo {
-    //subpass_id: pass::SubpassId,
-    color_index: Option<u8>,
+    view: n::ImageView,
     clear_value: command::ClearValue,
 }
 
 #[derive(Debug)]
 pub struct RenderPassCache {
     render_pass: n::RenderPass,
+    framebuffer: n::RawFramebuffer,
     attachments: Vec<AttachmentInfo>,
 }
 
@@ -183,6 +246,8 @@ pub struct RenderPassCache {
 struct TextureSlotInfo {
     tex_target: n::TextureTarget,
     sampler_index: Option<u8>,
+    // Bound texture and its sampled mip range.
+    levels: Option<(n::Texture, image::Level, image::Level)>,
 }
 
 // Cache current states of the command buffer
@@ -373,6 +438,7 @@ pub struct CommandBuffer {
     cur_subpass: pass::SubpassId,
 
     limits: Limits,
+    features: hal::Features,
     legacy_featues: info::LegacyFeatures,
     active_attribs: usize,
 }
@@ -382,6 +448,7 @@ impl CommandBuffer {
         fbo: Option<n::RawFramebuffer>,
         limits: Limits,
         memory: Arc<Mutex<BufferMemory>>,
+        features: hal::Features,
         legacy_featues: info::LegacyFeatures,
     ) -> Self {
         let (id, individual_reset) = {
@@ -418,6 +485,7 @@ impl CommandBuffer {
             cur_subpass: !0,
             limits,
             active_attribs: 0,
+            features,
             legacy_featues,
         }
     }
@@ -481,6 +549,35 @@ impl CommandBuffer {
         }
     }
 
+    // Push constants are emulated with plain uniforms of the bound program.
+    fn push_constants(&mut self, offset: u32, constants: &[u32]) {
+        let buffer = self.data.add(constants);
+
+        let uniforms = &self.cache.uniforms;
+        if uniforms.is_empty() {
+            log::error!("No push constant uniforms found in the bound program");
+            self.cache.error_state = true;
+            return;
+        }
+
+        let uniform = if offset == 0 {
+            // If offset is zero, we can just return the first item
+            // in our uniform list
+            uniforms.get(0).unwrap()
+        } else {
+            match uniforms.binary_search_by(|uniform| uniform.offset.cmp(&offset as _)) {
+                Ok(index) => uniforms.get(index).unwrap(),
+                Err(_) => {
+                    log::error!("No push constant uniform found at offset {}", offset);
+                    return;
+                }
+            }
+        }
+        .clone();
+
+        self.data.push_cmd(Command::BindUniform { uniform, buffer });
+    }
+
     pub(crate) fn bind_attributes(&mut self, first_instance: u32) {
         let Cache {
             ref attributes,
@@ -503,8 +600,11 @@ impl CommandBuffer {
 
             match vertex_buffer_descs.get(binding) {
                 Some(&Some(desc)) => {
-                    if let pso::VertexInputRate::Instance(_) = desc.rate {
-                        attribute.offset += desc.stride * first_instance as u32;
+                    // Each instance record is shared by `divisor` consecutive instances.
+                    if let pso::VertexInputRate::Instance(divisor) = desc.rate {
+                        if divisor != 0 {
+                            attribute.offset += desc.stride * (first_instance / divisor as u32);
+                        }
                     }
 
                     self.data.push_cmd(Command::BindAttribute(
@@ -519,38 +619,193 @@ impl CommandBuffer {
         }
     }
 
+    fn draw_indirect_impl(
+        &mut self,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        count_buffer: Option<(&n::Buffer, buffer::Offset)>,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
+    ) {
+        if !self
+            .legacy_featues
+            .contains(info::LegacyFeatures::INDIRECT_EXECUTION)
+        {
+            log::error!("Indirect drawing is not supported");
+            self.cache.error_state = true;
+            return;
+        }
+        if count_buffer.is_some() && !self.features.contains(hal::Features::DRAW_INDIRECT_COUNT) {
+            log::error!("Indirect draw count is not supported");
+            self.cache.error_state = true;
+            return;
+        }
+
+        self.bind_attributes(0);
+
+        let bounded_buffer = buffer.as_bound();
+        let count_buffer = count_buffer.map(|(buffer, offset)| {
+            let bounded = buffer.as_bound();
+            (bounded.raw, bounded.range.start + offset)
+        });
+        match self.cache.primitive {
+            Some(primitive) => {
+                self.data.push_cmd(Command::DrawIndirect {
+                    primitive,
+                    buffer: bounded_buffer.raw,
+                    offset: bounded_buffer.range.start + offset,
+                    count_buffer,
+                    draw_count,
+                    stride,
+                });
+            }
+            None => {
+                log::warn!("No primitive bound. An active pipeline needs to be bound before calling `draw_indirect`.");
+                self.cache.error_state = true;
+            }
+        }
+    }
+
+    fn draw_indexed_indirect_impl(
+        &mut self,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        count_buffer: Option<(&n::Buffer, buffer::Offset)>,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
+    ) {
+        if !self
+            .legacy_featues
+            .contains(info::LegacyFeatures::INDIRECT_EXECUTION)
+        {
+            log::error!("Indirect drawing is not supported");
+            self.cache.error_state = true;
+            return;
+        }
+        if count_buffer.is_some() && !self.features.contains(hal::Features::DRAW_INDIRECT_COUNT) {
+            log::error!("Indirect draw count is not supported");
+            self.cache.error_state = true;
+            return;
+        }
+
+        self.bind_attributes(0);
+
+        let (index_type, buffer_range) = match &self.cache.index_type_range {
+            Some((index_type, buffer_range)) => (index_type, buffer_range),
+            None => {
+                log::warn!("No index type bound. An index buffer needs to be bound before calling `draw_indexed_indirect`.");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+
+        // The first index of an indirect command is relative to the start of
+        // the GL buffer object, so the index buffer can't be sub-allocated.
+        if buffer_range.start != 0 {
+            log::error!("Indirect indexed drawing requires the index buffer to start at offset 0");
+            self.cache.error_state = true;
+            return;
+        }
+
+        let index_type = match index_type {
+            hal::IndexType::U16 => glow::UNSIGNED_SHORT,
+            hal::IndexType::U32 => glow::UNSIGNED_INT,
+        };
+
+        let bounded_buffer = buffer.as_bound();
+        let count_buffer = count_buffer.map(|(buffer, offset)| {
+            let bounded = buffer.as_bound();
+            (bounded.raw, bounded.range.start + offset)
+        });
+        match self.cache.primitive {
+            Some(primitive) => {
+                self.data.push_cmd(Command::DrawIndexedIndirect {
+                    primitive,
+                    index_type,
+                    buffer: bounded_buffer.raw,
+                    offset: bounded_buffer.range.start + offset,
+                    count_buffer,
+                    draw_count,
+                    stride,
+                });
+            }
+            None => {
+                log::warn!("No primitive bound. An active pipeline needs to be bound before calling `draw_indexed_indirect`.");
+                self.cache.error_state = true;
+            }
+        }
+    }
+
     fn begin_subpass(&mut self) {
         let state = self.pass_cache.as_ref().unwrap();
         let subpass = &state.render_pass.subpasses[self.cur_subpass as usize];
 
         // See `begin_renderpass_cache` for clearing strategy
 
-        // Bind draw buffers for mapping color output locations with
-        // framebuffer attachments.
-        let attachment_indices = subpass
+        // Only the attachments written by this subpass are attached, so input
+        // attachments are never sampled while being bound to the framebuffer.
+        let colors = subpass
             .color_attachments
             .iter()
-            .map(|&index| state.attachments[index].color_index.unwrap())
+            .map(|&index| state.attachments[index].view.clone())
             .collect();
+        let depth_stencil = subpass
+            .depth_stencil
+            .map(|index| state.attachments[index].view.clone());
+        self.data.push_cmd(Command::BindFramebuffer {
+            target: glow::DRAW_FRAMEBUFFER,
+            framebuffer: state.framebuffer,
+            colors,
+            depth_stencil,
+        });
+
+        // Bind draw buffers for mapping color output locations with
+        // framebuffer attachments.
+        let attachment_indices = (0..subpass.color_attachments.len() as u8).collect();
         self.data
             .push_cmd(Command::SetDrawColorBuffers(attachment_indices));
         self.data
             .push_cmd(Command::SetColorMask(None, pso::ColorMask::ALL));
 
-        for (rat, info) in state
+        for (index, (rat, info)) in state
             .render_pass
             .attachments
             .iter()
             .zip(state.attachments.iter())
+            .enumerate()
         {
-            //TODO: check if the subpass is one where we need this clear
+            // Load ops only apply to the first subpass using the attachment.
+            let first_use = state
+                .render_pass
+                .subpasses
+                .iter()
+                .position(|sp| sp.uses_attachment(index));
+            if first_use != Some(self.cur_subpass as usize) {
+                continue;
+            }
 
             // View format needs to be known at this point.
             // All attachments specified in the renderpass must have a valid,
             // matching image view bound in the framebuffer.
             let view_format = rat.format.unwrap();
 
-            match info.color_index {
+            let draw_color_index = subpass.color_attachments.iter().position(|&i| i == index);
+            let is_bound = if info.view.aspects().contains(Aspects::COLOR) {
+                draw_color_index.is_some()
+            } else {
+                subpass.depth_stencil == Some(index)
+            };
+            // Attachments that are only read by the subpass aren't attached to the framebuffer.
+            if !is_bound {
+                if rat.ops.load == pass::AttachmentLoadOp::Clear
+                    || rat.stencil_ops.load == pass::AttachmentLoadOp::Clear
+                {
+                    log::warn!("Unable to clear input-only attachment {}", index);
+                }
+                continue;
+            }
+
+            match draw_color_index {
                 // Clear color target
                 Some(draw_color_index) => {
                     if rat.ops.load != pass::AttachmentLoadOp::Clear {
@@ -664,19 +919,42 @@ impl CommandBuffer {
         I: Iterator<Item = &'a n::DescriptorSet>,
         J: Iterator<Item = command::DescriptorSetOffset>,
     {
-        if let Some(_) = offsets.next() {
-            log::warn!("Dynamic offsets are not supported yet");
-        }
-
         let mut dirty_textures = 0u32;
         let mut dirty_samplers = 0u32;
         let mut set = first_set as usize;
         for desc_set in sets {
-            for (binding_layout, new_binding) in
-                desc_set.layout.iter().zip(desc_set.bindings.iter())
-            {
-                let binding = layout.sets[set].bindings[binding_layout.binding as usize] as u32;
-                match *new_binding {
+            // Dynamic offsets are consumed in binding order, one per array element.
+            let mut dynamic_offsets = Vec::new();
+            for binding_layout in desc_set.layout.iter() {
+                if let pso::DescriptorType::Buffer {
+                    format:
+                        pso::BufferDescriptorFormat::Structured {
+                            dynamic_offset: true,
+                        },
+                    ..
+                } = binding_layout.ty
+                {
+                    for array_index in 0..binding_layout.count {
+                        let offset = match offsets.next() {
+                            Some(dynamic_offset) => dynamic_offset as i32,
+                            None => {
+                                log::error!(
+                                    "Missing dynamic offset for binding {}",
+                                    binding_layout.binding
+                                );
+                                0
+                            }
+                        };
+                        dynamic_offsets.push(((binding_layout.binding, array_index), offset));
+                    }
+                }
+            }
+
+            for entry in desc_set.bindings.iter() {
+                // Array elements occupy consecutive slots after the first one.
+                let binding = layout.sets[set].bindings[entry.binding as usize] as u32
+                    + entry.array_index as u32;
+                match entry.value {
                     n::DescSetBindings::Buffer {
                         register,
                         buffer,
@@ -686,21 +964,68 @@ impl CommandBuffer {
                         let bind_point = match register {
                             n::BindingRegister::UniformBuffers => glow::UNIFORM_BUFFER,
                             n::BindingRegister::StorageBuffers => glow::SHADER_STORAGE_BUFFER,
-                            n::BindingRegister::Textures => panic!("Wrong desc set binding"),
+                            n::BindingRegister::Textures | n::BindingRegister::Images => {
+                                panic!("Wrong desc set binding")
+                            }
                         };
+                        let dynamic_offset = dynamic_offsets
+                            .iter()
+                            .find(|&&(position, _)| position == (entry.binding, entry.array_index))
+                            .map_or(0, |&(_, dynamic_offset)| dynamic_offset);
                         self.data.push_cmd(Command::BindBufferRange(
                             bind_point,
                             binding,
                             buffer,
-                            offset as i32,
+                            offset as i32 + dynamic_offset,
                             size as i32,
                         ));
                     }
-                    n::DescSetBindings::Texture(texture, textype) => {
+                    n::DescSetBindings::Texture(texture, textype, ref levels) => {
                         dirty_textures |= 1 << binding;
                         self.cache.texture_slots[binding as usize].tex_target = textype;
                         self.data
                             .push_cmd(Command::BindTexture(binding, texture, textype));
+                        // Buffer textures have no mipmaps.
+                        if textype != glow::TEXTURE_BUFFER {
+                            // The mip range is texture state, so only one range
+                            // of a texture can be sampled at a time.
+                            let bound = (texture, levels.start, levels.end);
+                            let slots = self.cache.texture_slots.iter().enumerate();
+                            let conflict = slots.filter(|&(slot, _)| slot != binding as usize).any(
+                                |(_, info)| {
+                                    info.levels
+                                        .map_or(false, |other| other.0 == texture && other != bound)
+                                },
+                            );
+                            if conflict {
+                                log::error!(
+                                    "Views of {:?} with different mip ranges are bound at the same time",
+                                    texture
+                                );
+                            }
+                            self.cache.texture_slots[binding as usize].levels = Some(bound);
+                            self.data.push_cmd(Command::SetTextureLevels(
+                                binding,
+                                textype,
+                                levels.clone(),
+                            ));
+                        }
+                    }
+                    n::DescSetBindings::Image {
+                        texture,
+                        level,
+                        layer,
+                        access,
+                        format,
+                    } => {
+                        self.data.push_cmd(Command::BindImage {
+                            unit: binding,
+                            texture,
+                            level,
+                            layer,
+                            access,
+                            format,
+                        });
                     }
                     n::DescSetBindings::Sampler(sampler) => {
                         dirty_samplers |= 1 << binding;
@@ -777,14 +1102,17 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                         mask |= glow::SHADER_STORAGE_BARRIER_BIT;
                     }
                 }
+                // Images written through image stores may be sampled afterwards.
                 memory::Barrier::AllImages(access) => {
                     if access.start.contains(image::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
+                        mask |=
+                            glow::SHADER_IMAGE_ACCESS_BARRIER_BIT | glow::TEXTURE_FETCH_BARRIER_BIT;
                     }
                 }
                 memory::Barrier::Image { states, .. } => {
                     if states.start.0.contains(image::Access::SHADER_WRITE) {
-                        mask |= glow::SHADER_IMAGE_ACCESS_BARRIER_BIT;
+                        mask |=
+                            glow::SHADER_IMAGE_ACCESS_BARRIER_BIT | glow::TEXTURE_FETCH_BARRIER_BIT;
                     }
                 }
             }
@@ -826,56 +1154,74 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         //      select correct ClearBuffer variant.
         //    * Check for attachment loading clearing strategy
 
-        // TODO: store ops:
-        //   < GL 4.5: Ignore
-        //  >= GL 4.5: Invalidate framebuffer attachment when store op is `DONT_CARE`.
+        // Store ops: attachments with `DontCare` are invalidated in `end_render_pass`.
 
         // 2./3.
-        let mut attachments = Vec::new();
-        let mut colors = ArrayVec::new();
-        let mut depth_stencil = None;
-
-        for info in attachment_infos {
-            let view = info.image_view.clone();
-            let aspects = view.aspects();
-            let color_index = if aspects.contains(Aspects::COLOR) {
-                colors.push(view);
-                Some(colors.len() as u8 - 1)
-            } else {
-                depth_stencil = Some(view);
-                None
-            };
-            attachments.push(AttachmentInfo {
-                color_index,
+        let attachments = attachment_infos
+            .map(|info| AttachmentInfo {
+                view: info.image_view.clone(),
                 clear_value: info.clear_value,
             })
-        }
+            .collect();
 
         self.pass_cache = Some(RenderPassCache {
             render_pass: render_pass.clone(),
+            framebuffer: framebuffer.raw,
             attachments,
         });
 
-        //Note: we currently only expect one sub-pass.
-
-        self.data.push_cmd(Command::BindFramebuffer {
-            target: glow::DRAW_FRAMEBUFFER,
-            framebuffer: framebuffer.raw,
-            colors,
-            depth_stencil,
+        // sRGB encoding follows the color attachment formats of the pass,
+        // so passes can write linear data through a non-sRGB view.
+        let srgb = render_pass.attachments.iter().any(|attachment| {
+            attachment
+                .format
+                .map_or(false, |format| format.base_format().1 == ChannelType::Srgb)
         });
+        self.data.push_cmd(Command::SetFramebufferSrgb(srgb));
 
-        // Enter first subpass
+        // Enter first subpass, which binds the framebuffer
         self.cur_subpass = 0;
         self.begin_subpass();
     }
 
     unsafe fn next_subpass(&mut self, _contents: command::SubpassContents) {
-        unimplemented!()
+        self.cur_subpass += 1;
+        self.begin_subpass();
     }
 
     unsafe fn end_render_pass(&mut self) {
-        // TODO
+        let state = match self.pass_cache {
+            Some(ref state) => state,
+            None => return,
+        };
+
+        // Let tiled GPUs skip writing back attachments whose contents aren't needed.
+        // Only the attachments of the last subpass are still attached.
+        let subpass = &state.render_pass.subpasses[self.cur_subpass as usize];
+        let mut invalidated = ArrayVec::new();
+        for (draw_color_index, &index) in subpass.color_attachments.iter().enumerate() {
+            if state.render_pass.attachments[index].ops.store == pass::AttachmentStoreOp::DontCare {
+                invalidated.push(glow::COLOR_ATTACHMENT0 + draw_color_index as u32);
+            }
+        }
+        if let Some(index) = subpass.depth_stencil {
+            let rat = &state.render_pass.attachments[index];
+            // Without a known format, keep the attachment contents.
+            let (is_depth, is_stencil) = rat.format.map_or((false, false), |format| {
+                (format.is_depth(), format.is_stencil())
+            });
+            if is_depth && rat.ops.store == pass::AttachmentStoreOp::DontCare {
+                invalidated.push(glow::DEPTH_ATTACHMENT);
+            }
+            if is_stencil && rat.stencil_ops.store == pass::AttachmentStoreOp::DontCare {
+                invalidated.push(glow::STENCIL_ATTACHMENT);
+            }
+        }
+
+        if !invalidated.is_empty() {
+            self.data
+                .push_cmd(Command::InvalidateAttachments(invalidated));
+        }
     }
 
     unsafe fn clear_image<T>(
@@ -907,12 +1253,14 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                         raw,
                         level_count,
                         layer_count,
+                        internal_format,
                         ..
                     } => {
                         let is_3d = layer_count == 1; //TODO?
                         n::ImageView::Texture {
                             target,
                             raw,
+                            format: internal_format,
                             is_3d,
                             sub: image::SubresourceRange {
                                 aspects: Aspects::COLOR,
@@ -921,6 +1269,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                                 level_start: 0,
                                 level_count: Some(level_count),
                             },
+                            view: None,
                         }
                     }
                 };
@@ -973,29 +1322,82 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
     unsafe fn resolve_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _regions: T,
+        regions: T,
     ) where
         T: Iterator<Item = command::ImageResolve>,
     {
-        unimplemented!()
+        let old_size = self.data.buf.size;
+
+        // Blitting from a multisampled framebuffer resolves the samples.
+        for r in regions {
+            self.data.push_cmd(Command::BlitImage {
+                src_image: src.object_type,
+                dst_image: dst.object_type,
+                filter: glow::NEAREST,
+                data: command::ImageBlit {
+                    src_subresource: r.src_subresource,
+                    src_bounds: r.src_offset.into_bounds(&r.extent),
+                    dst_subresource: r.dst_subresource,
+                    dst_bounds: r.dst_offset.into_bounds(&r.extent),
+                },
+            });
+        }
+
+        if self.data.buf.size == old_size {
+            log::error!("At least one region must be specified");
+        }
     }
 
     unsafe fn blit_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _filter: image::Filter,
-        _regions: T,
+        filter: image::Filter,
+        regions: T,
     ) where
         T: Iterator<Item = command::ImageBlit>,
     {
-        log::error!("Blit is not implemented");
+        let old_size = self.data.buf.size;
+
+        let filter = match filter {
+            image::Filter::Nearest => glow::NEAREST,
+            image::Filter::Linear => glow::LINEAR,
+        };
+        for r in regions {
+            self.data.push_cmd(Command::BlitImage {
+                src_image: src.object_type,
+                dst_image: dst.object_type,
+                filter,
+                data: r,
+            });
+        }
+
+        if self.data.buf.size == old_size {
+            log::error!("At least one region must be specified");
+        }
+    }
+
+    unsafe fn generate_mipmaps(&mut self, image: &n::Image) {
+        match image.object_type {
+            n::ImageType::Texture {
+                target,
+                raw,
+                level_count,
+                ..
+            } => {
+                self.data
+                    .push_cmd(Command::GenerateMipmap(raw, target, level_count));
+            }
+            n::ImageType::Renderbuffer { .. } => {
+                log::error!("Mipmaps can't be generated for renderbuffers");
+            }
+        }
     }
 
     unsafe fn bind_index_buffer(
@@ -1153,12 +1555,12 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         log::warn!("Depth bounds test is not supported");
     }
 
-    unsafe fn set_line_width(&mut self, _width: f32) {
-        unimplemented!()
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.data.push_cmd(Command::SetLineWidth(width));
     }
 
-    unsafe fn set_depth_bias(&mut self, _depth_bias: pso::DepthBias) {
-        unimplemented!()
+    unsafe fn set_depth_bias(&mut self, depth_bias: pso::DepthBias) {
+        self.data.push_cmd(Command::SetDepthBias(depth_bias));
     }
 
     unsafe fn bind_graphics_pipeline(&mut self, pipeline: &n::GraphicsPipeline) {
@@ -1173,6 +1575,9 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
             }
         }
 
+        self.data
+            .push_cmd(Command::SetPrimitiveRestart(pipeline.primitive_restart));
+
         if self.cache.program != Some(pipeline.program) {
             self.cache.program = Some(pipeline.program);
             self.data.push_cmd(Command::BindProgram(pipeline.program));
@@ -1188,6 +1593,8 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         self.data.push_cmd(Command::BindRasterizer {
             rasterizer: pipeline.rasterizer,
         });
+        self.data
+            .push_cmd(Command::BindMultisampling(pipeline.multisampling.clone()));
         self.data
             .push_cmd(Command::BindDepth(pipeline.depth.map(|d| d.fun)));
         self.data.push_cmd(Command::SetDepthMask(
@@ -1244,6 +1651,8 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
             self.cache.program = Some(pipeline.program);
             self.data.push_cmd(Command::BindProgram(pipeline.program));
         }
+
+        self.cache.uniforms = pipeline.uniforms.clone();
     }
 
     unsafe fn bind_compute_descriptor_sets<'a, I, J>(
@@ -1311,12 +1720,14 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
         for r in regions {
             let cmd = match dst.object_type {
-                n::ImageType::Renderbuffer { raw, format } => Command::CopyImageToRenderbuffer {
-                    src_image: src.object_type,
-                    dst_renderbuffer: raw,
-                    dst_format: format,
-                    data: r,
-                },
+                n::ImageType::Renderbuffer { raw, format, .. } => {
+                    Command::CopyImageToRenderbuffer {
+                        src_image: src.object_type,
+                        dst_renderbuffer: raw,
+                        dst_format: format,
+                        data: r,
+                    }
+                }
                 n::ImageType::Texture { raw, target, .. } => {
                     Command::CopyImageToTexture(src.object_type, raw, target, r)
                 }
@@ -1347,6 +1758,20 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                 n::ImageType::Renderbuffer { raw, .. } => {
                     Command::CopyBufferToRenderbuffer(src_bounded_buffer.raw, raw, r)
                 }
+                n::ImageType::Texture {
+                    raw,
+                    target,
+                    format,
+                    ..
+                } if dst.format_desc.is_compressed() => Command::CopyBufferToCompressedTexture {
+                    src_buffer: src_bounded_buffer.raw,
+                    dst_texture: raw,
+                    texture_target: target,
+                    texture_format: format,
+                    block_size: dst.format_desc.bits as u32 / 8,
+                    block_dim: dst.format_desc.dim,
+                    data: r,
+                },
                 n::ImageType::Texture {
                     raw,
                     target,
@@ -1385,9 +1810,17 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         for mut r in regions {
             r.buffer_offset += dst_bounded_buffer.range.start;
             let cmd = match src.object_type {
-                n::ImageType::Renderbuffer { raw, .. } => {
-                    Command::CopyRenderbufferToBuffer(raw, dst_bounded_buffer.raw, r)
-                }
+                n::ImageType::Renderbuffer {
+                    raw,
+                    format,
+                    pixel_type,
+                } => Command::CopyRenderbufferToBuffer {
+                    src_renderbuffer: raw,
+                    texture_format: format,
+                    pixel_type,
+                    dst_buffer: dst_bounded_buffer.raw,
+                    data: r,
+                },
                 n::ImageType::Texture {
                     raw,
                     target,
@@ -1399,6 +1832,7 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
                     texture_target: target,
                     texture_format: format,
                     pixel_type: pixel_type,
+                    texel_size: src.format_desc.bits as u32 / 8,
                     dst_buffer: dst_bounded_buffer.raw,
                     data: r,
                 },
@@ -1499,46 +1933,58 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
 
     unsafe fn draw_indirect(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _draw_count: hal::DrawCount,
-        _stride: buffer::Stride,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
     ) {
-        unimplemented!()
+        self.draw_indirect_impl(buffer, offset, None, draw_count, stride);
     }
 
     unsafe fn draw_indexed_indirect(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _draw_count: hal::DrawCount,
-        _stride: buffer::Stride,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: buffer::Stride,
     ) {
-        unimplemented!()
+        self.draw_indexed_indirect_impl(buffer, offset, None, draw_count, stride);
     }
 
     unsafe fn draw_indirect_count(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _count_buffer: &n::Buffer,
-        _count_buffer_offset: buffer::Offset,
-        _max_draw_count: u32,
-        _stride: buffer::Stride,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        count_buffer: &n::Buffer,
+        count_buffer_offset: buffer::Offset,
+        max_draw_count: u32,
+        stride: buffer::Stride,
     ) {
-        unimplemented!()
+        self.draw_indirect_impl(
+            buffer,
+            offset,
+            Some((count_buffer, count_buffer_offset)),
+            max_draw_count,
+            stride,
+        );
     }
 
     unsafe fn draw_indexed_indirect_count(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _count_buffer: &n::Buffer,
-        _count_buffer_offset: buffer::Offset,
-        _max_draw_count: u32,
-        _stride: buffer::Stride,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        count_buffer: &n::Buffer,
+        count_buffer_offset: buffer::Offset,
+        max_draw_count: u32,
+        stride: buffer::Stride,
     ) {
-        unimplemented!()
+        self.draw_indexed_indirect_impl(
+            buffer,
+            offset,
+            Some((count_buffer, count_buffer_offset)),
+            max_draw_count,
+            stride,
+        );
     }
 
     unsafe fn draw_mesh_tasks(&mut self, _: u32, _: u32) {
@@ -1610,6 +2056,21 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        _buffer: &n::Buffer,
+        _offset: buffer::Offset,
+        _inverted: bool,
+    ) {
+        // `glBeginConditionalRender` only predicates on query objects, not on
+        // buffer contents, so the feature isn't exposed.
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
     unsafe fn push_graphics_constants(
         &mut self,
         _layout: &n::PipelineLayout,
@@ -1617,35 +2078,16 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         offset: u32,
         constants: &[u32],
     ) {
-        let buffer = self.data.add(constants);
-
-        let uniforms = &self.cache.uniforms;
-        if uniforms.is_empty() {
-            unimplemented!()
-        }
-
-        let uniform = if offset == 0 {
-            // If offset is zero, we can just return the first item
-            // in our uniform list
-            uniforms.get(0).unwrap()
-        } else {
-            match uniforms.binary_search_by(|uniform| uniform.offset.cmp(&offset as _)) {
-                Ok(index) => uniforms.get(index).unwrap(),
-                Err(_) => panic!("No uniform found at offset: {}", offset),
-            }
-        }
-        .clone();
-
-        self.data.push_cmd(Command::BindUniform { uniform, buffer });
+        self.push_constants(offset, constants);
     }
 
     unsafe fn push_compute_constants(
         &mut self,
         _layout: &n::PipelineLayout,
-        _offset: u32,
-        _constants: &[u32],
+        offset: u32,
+        constants: &[u32],
     ) {
-        unimplemented!()
+        self.push_constants(offset, constants);
     }
 
     unsafe fn execute_commands<'a, T>(&mut self, _buffers: T)
@@ -1655,13 +2097,15 @@ impl command::CommandBuffer<Backend> for CommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn insert_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
+        let name_ptr = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::InsertDebugMarker(name_ptr));
     }
-    unsafe fn begin_debug_marker(&mut self, _name: &str, _color: u32) {
-        //TODO
+    unsafe fn begin_debug_marker(&mut self, name: &str, _color: u32) {
+        let name_ptr = self.data.add_raw(name.as_bytes());
+        self.data.push_cmd(Command::PushDebugGroup(name_ptr));
     }
     unsafe fn end_debug_marker(&mut self) {
-        //TODO
+        self.data.push_cmd(Command::PopDebugGroup);
     }
 }
@@ -1,6 +1,47 @@
 use crate::native::VertexAttribFunction;
 use hal::{format::Format, image as i, pso};
 
+// `glow` doesn't define the S3TC formats (`GL_EXT_texture_compression_s3tc`
+// and `GL_EXT_texture_sRGB`).
+const COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1_EXT: u32 = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT: u32 = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT: u32 = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT: u32 = 0x8C4F;
+
+// `glow` doesn't define the ASTC formats (`GL_KHR_texture_compression_astc_ldr`).
+const COMPRESSED_RGBA_ASTC_4x4_KHR: u32 = 0x93B0;
+const COMPRESSED_RGBA_ASTC_5x4_KHR: u32 = 0x93B1;
+const COMPRESSED_RGBA_ASTC_5x5_KHR: u32 = 0x93B2;
+const COMPRESSED_RGBA_ASTC_6x5_KHR: u32 = 0x93B3;
+const COMPRESSED_RGBA_ASTC_6x6_KHR: u32 = 0x93B4;
+const COMPRESSED_RGBA_ASTC_8x5_KHR: u32 = 0x93B5;
+const COMPRESSED_RGBA_ASTC_8x6_KHR: u32 = 0x93B6;
+const COMPRESSED_RGBA_ASTC_8x8_KHR: u32 = 0x93B7;
+const COMPRESSED_RGBA_ASTC_10x5_KHR: u32 = 0x93B8;
+const COMPRESSED_RGBA_ASTC_10x6_KHR: u32 = 0x93B9;
+const COMPRESSED_RGBA_ASTC_10x8_KHR: u32 = 0x93BA;
+const COMPRESSED_RGBA_ASTC_10x10_KHR: u32 = 0x93BB;
+const COMPRESSED_RGBA_ASTC_12x10_KHR: u32 = 0x93BC;
+const COMPRESSED_RGBA_ASTC_12x12_KHR: u32 = 0x93BD;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR: u32 = 0x93D0;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_5x4_KHR: u32 = 0x93D1;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_5x5_KHR: u32 = 0x93D2;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_6x5_KHR: u32 = 0x93D3;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_6x6_KHR: u32 = 0x93D4;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_8x5_KHR: u32 = 0x93D5;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_8x6_KHR: u32 = 0x93D6;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_8x8_KHR: u32 = 0x93D7;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_10x5_KHR: u32 = 0x93D8;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_10x6_KHR: u32 = 0x93D9;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_10x8_KHR: u32 = 0x93DA;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_10x10_KHR: u32 = 0x93DB;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_12x10_KHR: u32 = 0x93DC;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_12x12_KHR: u32 = 0x93DD;
+
 /*
 pub fn _image_kind_to_gl(kind: i::Kind) -> t::GLenum {
     match kind {
@@ -16,6 +57,19 @@ pub fn _image_kind_to_gl(kind: i::Kind) -> t::GLenum {
     }
 }*/
 
+/// Target of a texture view of the given kind, aliasing a texture of the `original` target.
+pub fn view_kind_to_target(kind: i::ViewKind, original: u32) -> u32 {
+    match (kind, original) {
+        (_, glow::TEXTURE_2D_MULTISAMPLE) | (_, glow::TEXTURE_2D_MULTISAMPLE_ARRAY) => original,
+        (i::ViewKind::D1, _) | (i::ViewKind::D1Array, _) => original,
+        (i::ViewKind::D2, _) => glow::TEXTURE_2D,
+        (i::ViewKind::D2Array, _) => glow::TEXTURE_2D_ARRAY,
+        (i::ViewKind::D3, _) => glow::TEXTURE_3D,
+        (i::ViewKind::Cube, _) => glow::TEXTURE_CUBE_MAP,
+        (i::ViewKind::CubeArray, _) => glow::TEXTURE_CUBE_MAP_ARRAY,
+    }
+}
+
 pub fn filter_to_gl(mag: i::Filter, min: i::Filter, mip: i::Filter) -> (u32, u32) {
     use hal::image::Filter::*;
 
@@ -85,9 +139,22 @@ impl FormatDescription {
             va_fun,
         }
     }
+
+    fn compressed(tex_internal: u32, num_components: u8) -> Self {
+        // Compressed data is uploaded with the internal format directly.
+        FormatDescription::new(
+            tex_internal,
+            tex_internal,
+            glow::UNSIGNED_BYTE,
+            num_components,
+            VertexAttribFunction::Float,
+        )
+    }
 }
 
-pub fn describe_format(format: Format) -> Option<FormatDescription> {
+/// Describe how `format` maps onto GL. GLES takes some internal formats
+/// differently from desktop GL, so `is_embedded` selects the ES variant.
+pub fn describe_format(format: Format, is_embedded: bool) -> Option<FormatDescription> {
     use crate::native::VertexAttribFunction::*;
     use hal::format::Format::*;
     let _ = Double; //mark as used
@@ -132,7 +199,16 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
             4,
             Float,
         ),
-        Bgra8Unorm => FormatDescription::new(glow::BGRA, glow::BGRA, glow::UNSIGNED_BYTE, 4, Float),
+        // GLES (EXT_texture_format_BGRA8888) requires BGRA_EXT as the internal format.
+        Bgra8Unorm if is_embedded => {
+            FormatDescription::new(glow::BGRA, glow::BGRA, glow::UNSIGNED_BYTE, 4, Float)
+        }
+        // On desktop GL, BGRA is only an external layout, the data is stored as sized RGBA.
+        Bgra8Unorm => {
+            FormatDescription::new(glow::RGBA8, glow::BGRA, glow::UNSIGNED_BYTE, 4, Float)
+        }
+        // GLES has no sRGB variant of BGRA_EXT.
+        Bgra8Srgb if is_embedded => return None,
         Bgra8Srgb => FormatDescription::new(
             glow::SRGB8_ALPHA8,
             glow::BGRA,
@@ -214,6 +290,13 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
             FormatDescription::new(glow::RGBA32I, glow::RGBA_INTEGER, glow::INT, 4, Integer)
         }
         Rgba32Sfloat => FormatDescription::new(glow::RGBA32F, glow::RGBA, glow::FLOAT, 4, Float),
+        E5b9g9r9Ufloat => FormatDescription::new(
+            glow::RGB9_E5,
+            glow::RGB,
+            glow::UNSIGNED_INT_5_9_9_9_REV,
+            3,
+            Float,
+        ),
         S8Uint => FormatDescription::new(glow::R8, glow::RED, glow::UNSIGNED_BYTE, 1, Integer),
         D16Unorm => FormatDescription::new(
             glow::DEPTH_COMPONENT16,
@@ -225,7 +308,7 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
         D24UnormS8Uint => FormatDescription::new(
             glow::DEPTH24_STENCIL8,
             glow::DEPTH_STENCIL,
-            glow::UNSIGNED_INT,
+            glow::UNSIGNED_INT_24_8,
             2,
             Float,
         ),
@@ -239,8 +322,8 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
         D32SfloatS8Uint => FormatDescription::new(
             glow::DEPTH32F_STENCIL8,
             glow::DEPTH_STENCIL,
-            glow::UNSIGNED_INT,
-            1,
+            glow::FLOAT_32_UNSIGNED_INT_24_8_REV,
+            2,
             Float,
         ),
         X8D24Unorm => FormatDescription::new(
@@ -250,11 +333,197 @@ pub fn describe_format(format: Format) -> Option<FormatDescription> {
             2,
             Float,
         ),
+        Bc1RgbUnorm => FormatDescription::compressed(COMPRESSED_RGB_S3TC_DXT1_EXT, 3),
+        Bc1RgbSrgb => FormatDescription::compressed(COMPRESSED_SRGB_S3TC_DXT1_EXT, 3),
+        Bc1RgbaUnorm => FormatDescription::compressed(COMPRESSED_RGBA_S3TC_DXT1_EXT, 4),
+        Bc1RgbaSrgb => FormatDescription::compressed(COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT, 4),
+        Bc2Unorm => FormatDescription::compressed(COMPRESSED_RGBA_S3TC_DXT3_EXT, 4),
+        Bc2Srgb => FormatDescription::compressed(COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT, 4),
+        Bc3Unorm => FormatDescription::compressed(COMPRESSED_RGBA_S3TC_DXT5_EXT, 4),
+        Bc3Srgb => FormatDescription::compressed(COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT, 4),
+        Bc4Unorm => FormatDescription::compressed(glow::COMPRESSED_RED_RGTC1, 1),
+        Bc4Snorm => FormatDescription::compressed(glow::COMPRESSED_SIGNED_RED_RGTC1, 1),
+        Bc5Unorm => FormatDescription::compressed(glow::COMPRESSED_RG_RGTC2, 2),
+        Bc5Snorm => FormatDescription::compressed(glow::COMPRESSED_SIGNED_RG_RGTC2, 2),
+        Bc6hUfloat => FormatDescription::compressed(glow::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT, 3),
+        Bc6hSfloat => FormatDescription::compressed(glow::COMPRESSED_RGB_BPTC_SIGNED_FLOAT, 3),
+        Bc7Unorm => FormatDescription::compressed(glow::COMPRESSED_RGBA_BPTC_UNORM, 4),
+        Bc7Srgb => FormatDescription::compressed(glow::COMPRESSED_SRGB_ALPHA_BPTC_UNORM, 4),
+        Etc2R8g8b8Unorm => FormatDescription::compressed(glow::COMPRESSED_RGB8_ETC2, 3),
+        Etc2R8g8b8Srgb => FormatDescription::compressed(glow::COMPRESSED_SRGB8_ETC2, 3),
+        Etc2R8g8b8a1Unorm => {
+            FormatDescription::compressed(glow::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2, 4)
+        }
+        Etc2R8g8b8a1Srgb => {
+            FormatDescription::compressed(glow::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2, 4)
+        }
+        Etc2R8g8b8a8Unorm => FormatDescription::compressed(glow::COMPRESSED_RGBA8_ETC2_EAC, 4),
+        Etc2R8g8b8a8Srgb => {
+            FormatDescription::compressed(glow::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC, 4)
+        }
+        EacR11Unorm => FormatDescription::compressed(glow::COMPRESSED_R11_EAC, 1),
+        EacR11Snorm => FormatDescription::compressed(glow::COMPRESSED_SIGNED_R11_EAC, 1),
+        EacR11g11Unorm => FormatDescription::compressed(glow::COMPRESSED_RG11_EAC, 2),
+        EacR11g11Snorm => FormatDescription::compressed(glow::COMPRESSED_SIGNED_RG11_EAC, 2),
+        Astc4x4Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_4x4_KHR, 4),
+        Astc4x4Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR, 4),
+        Astc5x4Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_5x4_KHR, 4),
+        Astc5x4Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_5x4_KHR, 4),
+        Astc5x5Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_5x5_KHR, 4),
+        Astc5x5Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_5x5_KHR, 4),
+        Astc6x5Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_6x5_KHR, 4),
+        Astc6x5Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_6x5_KHR, 4),
+        Astc6x6Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_6x6_KHR, 4),
+        Astc6x6Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_6x6_KHR, 4),
+        Astc8x5Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_8x5_KHR, 4),
+        Astc8x5Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_8x5_KHR, 4),
+        Astc8x6Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_8x6_KHR, 4),
+        Astc8x6Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_8x6_KHR, 4),
+        Astc8x8Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_8x8_KHR, 4),
+        Astc8x8Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_8x8_KHR, 4),
+        Astc10x5Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_10x5_KHR, 4),
+        Astc10x5Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_10x5_KHR, 4),
+        Astc10x6Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_10x6_KHR, 4),
+        Astc10x6Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_10x6_KHR, 4),
+        Astc10x8Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_10x8_KHR, 4),
+        Astc10x8Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_10x8_KHR, 4),
+        Astc10x10Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_10x10_KHR, 4),
+        Astc10x10Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_10x10_KHR, 4),
+        Astc12x10Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_12x10_KHR, 4),
+        Astc12x10Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_12x10_KHR, 4),
+        Astc12x12Unorm => FormatDescription::compressed(COMPRESSED_RGBA_ASTC_12x12_KHR, 4),
+        Astc12x12Srgb => FormatDescription::compressed(COMPRESSED_SRGB8_ALPHA8_ASTC_12x12_KHR, 4),
 
         _ => return None,
     })
 }
 
+/// Returns the device feature required to use a block-compressed format,
+/// or `None` if the format isn't compressed.
+pub fn compressed_format_feature(format: Format) -> Option<hal::Features> {
+    use hal::format::SurfaceType::*;
+
+    match format.base_format().0 {
+        BC1_RGB | BC1_RGBA | BC2 | BC3 | BC4 | BC5 | BC6 | BC7 => Some(hal::Features::FORMAT_BC),
+        ETC2_R8_G8_B8 | ETC2_R8_G8_B8_A1 | ETC2_R8_G8_B8_A8 | EAC_R11 | EAC_R11_G11 => {
+            Some(hal::Features::FORMAT_ETC2)
+        }
+        ASTC_4x4 | ASTC_5x4 | ASTC_5x5 | ASTC_6x5 | ASTC_6x6 | ASTC_8x5 | ASTC_8x6 | ASTC_8x8
+        | ASTC_10x5 | ASTC_10x6 | ASTC_10x8 | ASTC_10x10 | ASTC_12x10 | ASTC_12x12 => {
+            Some(hal::Features::FORMAT_ASTC_LDR)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the internal format is color-renderable, following the tables of
+/// OpenGL 4.6 (8.12) and OpenGL ES 3.2 (8.10). On GLES, the float formats are
+/// only renderable with `float_renderable` (ES 3.2 or `GL_EXT_color_buffer_float`).
+pub fn is_color_renderable(
+    internal_format: u32,
+    is_embedded: bool,
+    float_renderable: bool,
+) -> bool {
+    match internal_format {
+        glow::R8_SNORM
+        | glow::RG8_SNORM
+        | glow::RGB8_SNORM
+        | glow::RGBA8_SNORM
+        | glow::R16_SNORM
+        | glow::RG16_SNORM
+        | glow::RGB16_SNORM
+        | glow::RGBA16_SNORM
+        | glow::RGB9_E5 => false,
+        _ if !is_embedded => true,
+        glow::R8
+        | glow::RG8
+        | glow::RGB8
+        | glow::RGB565
+        | glow::RGBA4
+        | glow::RGB5_A1
+        | glow::RGBA8
+        | glow::RGB10_A2
+        | glow::RGB10_A2UI
+        | glow::SRGB8_ALPHA8
+        | glow::R8I
+        | glow::R8UI
+        | glow::R16I
+        | glow::R16UI
+        | glow::R32I
+        | glow::R32UI
+        | glow::RG8I
+        | glow::RG8UI
+        | glow::RG16I
+        | glow::RG16UI
+        | glow::RG32I
+        | glow::RG32UI
+        | glow::RGBA8I
+        | glow::RGBA8UI
+        | glow::RGBA16I
+        | glow::RGBA16UI
+        | glow::RGBA32I
+        | glow::RGBA32UI => true,
+        // `GL_EXT_texture_format_BGRA8888`
+        glow::BGRA => true,
+        glow::R16F
+        | glow::RG16F
+        | glow::RGBA16F
+        | glow::R32F
+        | glow::RG32F
+        | glow::RGBA32F
+        | glow::R11F_G11F_B10F => float_renderable,
+        _ => false,
+    }
+}
+
+/// Whether the internal format can be used for image load/store, following
+/// the tables of OpenGL 4.6 (8.27) and OpenGL ES 3.2 (8.27).
+pub fn is_storage_format(internal_format: u32, is_embedded: bool) -> bool {
+    match internal_format {
+        glow::RGBA32F
+        | glow::RGBA16F
+        | glow::R32F
+        | glow::RGBA32UI
+        | glow::RGBA16UI
+        | glow::RGBA8UI
+        | glow::R32UI
+        | glow::RGBA32I
+        | glow::RGBA16I
+        | glow::RGBA8I
+        | glow::R32I
+        | glow::RGBA8
+        | glow::RGBA8_SNORM => true,
+        _ if is_embedded => false,
+        glow::RG32F
+        | glow::RG16F
+        | glow::R11F_G11F_B10F
+        | glow::R16F
+        | glow::RGB10_A2UI
+        | glow::RG32UI
+        | glow::RG16UI
+        | glow::RG8UI
+        | glow::R16UI
+        | glow::R8UI
+        | glow::RG32I
+        | glow::RG16I
+        | glow::RG8I
+        | glow::R16I
+        | glow::R8I
+        | glow::RGBA16
+        | glow::RGB10_A2
+        | glow::RG16
+        | glow::RG8
+        | glow::R16
+        | glow::R8
+        | glow::RGBA16_SNORM
+        | glow::RG16_SNORM
+        | glow::RG8_SNORM
+        | glow::R16_SNORM
+        | glow::R8_SNORM => true,
+        _ => false,
+    }
+}
+
 #[cfg(feature = "cross")]
 pub fn map_naga_stage_to_cross(stage: naga::ShaderStage) -> spirv_cross::spirv::ExecutionModel {
     use spirv_cross::spirv::ExecutionModel as Em;
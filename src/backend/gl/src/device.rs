@@ -1,5 +1,5 @@
 use crate::{
-    command as cmd, conv,
+    command as cmd, conv, ext,
     info::LegacyFeatures,
     native as n,
     pool::{BufferMemory, CommandPool, OwnedBuffer},
@@ -36,6 +36,28 @@ fn create_fbo_internal(
     }
 }
 
+// Binding of an image view sampled by shaders.
+fn sampled_binding(view: &n::ImageView) -> n::DescSetBindings {
+    match *view {
+        n::ImageView::Texture {
+            view: Some((target, raw)),
+            ref sub,
+            ..
+        } => n::DescSetBindings::Texture(raw, target, 0..sub.level_count.unwrap()),
+        n::ImageView::Texture {
+            target,
+            raw,
+            ref sub,
+            ..
+        } => n::DescSetBindings::Texture(
+            raw,
+            target,
+            sub.level_start..sub.level_start + sub.level_count.unwrap(),
+        ),
+        n::ImageView::Renderbuffer { .. } => panic!("Texture doesn't support shader binding"),
+    }
+}
+
 struct CompilationContext<'a> {
     layout: &'a n::PipelineLayout,
     sampler_map: &'a mut n::SamplerBindMap,
@@ -92,6 +114,121 @@ impl Device {
         }
     }
 
+    /// Flatten the descriptor set bindings of a SPIR-V module into the GL
+    /// binding slots of `layout`, since `GL_ARB_gl_spirv` has no descriptor sets.
+    fn remap_spirv_bindings(
+        words: &mut [u32],
+        layout: &n::PipelineLayout,
+    ) -> Result<(), d::ShaderError> {
+        const HEADER_LEN: usize = 5;
+        const OP_DECORATE: u32 = 71;
+        const DECORATION_BINDING: u32 = 33;
+        const DECORATION_DESCRIPTOR_SET: u32 = 34;
+
+        let mut sets = FastHashMap::<u32, u32>::default();
+        let mut binding_words = Vec::new();
+        let mut offset = HEADER_LEN;
+        while offset < words.len() {
+            let word_count = (words[offset] >> 16) as usize;
+            let opcode = words[offset] & 0xFFFF;
+            if word_count == 0 || offset + word_count > words.len() {
+                return Err(d::ShaderError::CompilationFailed(
+                    "Malformed SPIR-V instruction".to_string(),
+                ));
+            }
+            if opcode == OP_DECORATE && word_count == 4 {
+                let target = words[offset + 1];
+                match words[offset + 2] {
+                    DECORATION_DESCRIPTOR_SET => {
+                        sets.insert(target, words[offset + 3]);
+                        words[offset + 3] = 0;
+                    }
+                    DECORATION_BINDING => binding_words.push((target, offset + 3)),
+                    _ => {}
+                }
+            }
+            offset += word_count;
+        }
+
+        for (target, index) in binding_words {
+            let set = sets.get(&target).cloned().unwrap_or(0) as usize;
+            let slot = layout
+                .sets
+                .get(set)
+                .and_then(|set_info| set_info.bindings.get(words[index] as usize))
+                .ok_or_else(|| {
+                    d::ShaderError::CompilationFailed(format!(
+                        "Binding {} of set {} is not in the pipeline layout",
+                        words[index], set
+                    ))
+                })?;
+            words[index] = *slot as u32;
+        }
+        Ok(())
+    }
+
+    fn create_shader_module_spirv(
+        &self,
+        ep: &pso::EntryPoint<B>,
+        stage: naga::ShaderStage,
+        layout: &n::PipelineLayout,
+    ) -> Result<n::Shader, d::ShaderError> {
+        let gl = &self.share.context;
+        let target = match stage {
+            naga::ShaderStage::Vertex => glow::VERTEX_SHADER,
+            naga::ShaderStage::Fragment => glow::FRAGMENT_SHADER,
+            naga::ShaderStage::Compute => glow::COMPUTE_SHADER,
+        };
+
+        let mut words = ep.module.spv.clone();
+        Self::remap_spirv_bindings(&mut words, layout)?;
+        let binary = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+
+        let (constant_ids, constant_values): (Vec<u32>, Vec<u32>) = ep
+            .specialization
+            .constants
+            .iter()
+            .filter_map(|constant| {
+                let data = &ep.specialization.data
+                    [constant.range.start as usize..constant.range.end as usize];
+                match *data {
+                    [a, b, c, d] => Some((constant.id, u32::from_le_bytes([a, b, c, d]))),
+                    [a] => Some((constant.id, a as u32)),
+                    _ => {
+                        log::warn!(
+                            "Unsupported size of specialization constant {}",
+                            constant.id
+                        );
+                        None
+                    }
+                }
+            })
+            .unzip();
+
+        let name =
+            unsafe { gl.create_shader(target) }.map_err(d::ShaderError::CompilationFailed)?;
+        unsafe {
+            gl.shader_binary(&[name], glow::SHADER_BINARY_FORMAT_SPIR_V, &binary);
+            gl.specialize_shader(name, ep.entry, &constant_ids, &constant_values);
+        }
+        log::info!("\tSpecialized SPIR-V shader {:?}", name);
+
+        let compiled_ok = unsafe { gl.get_shader_compile_status(name) };
+        let log = unsafe { gl.get_shader_info_log(name) };
+        if compiled_ok {
+            if !log.is_empty() {
+                log::warn!("\tLog: {}", log);
+            }
+            Ok(name)
+        } else {
+            unsafe { gl.delete_shader(name) };
+            Err(d::ShaderError::CompilationFailed(log))
+        }
+    }
+
     fn create_shader_module_raw(
         gl: &GlContainer,
         shader: &str,
@@ -126,12 +263,100 @@ impl Device {
         }
     }
 
+    /// Array elements of a descriptor set layout, starting at the given binding
+    /// and element, and spilling over into the following bindings.
+    fn descriptor_positions(
+        layout: &n::DescriptorSetLayout,
+        binding: pso::DescriptorBinding,
+        array_offset: pso::DescriptorArrayIndex,
+    ) -> impl Iterator<Item = (pso::DescriptorBinding, pso::DescriptorArrayIndex)> + '_ {
+        let start = layout
+            .binary_search_by_key(&binding, |b| b.binding)
+            .unwrap();
+        layout[start..]
+            .iter()
+            .flat_map(|b| (0..b.count).map(move |index| (b.binding, index)))
+            .skip(array_offset)
+    }
+
+    /// Key of a program in the pipeline cache, derived from everything that
+    /// affects the generated shader code. Modules without SPIR-V can't be keyed.
+    fn program_cache_key(
+        shaders: &[(naga::ShaderStage, Option<&pso::EntryPoint<B>>)],
+        layout: &n::PipelineLayout,
+    ) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = fxhash::FxHasher64::default();
+        for &(stage, point_maybe) in shaders {
+            if let Some(point) = point_maybe {
+                if point.module.spv.is_empty() {
+                    return None;
+                }
+                (stage as u32).hash(&mut hasher);
+                point.entry.hash(&mut hasher);
+                point.module.spv.hash(&mut hasher);
+                point.specialization.constants.hash(&mut hasher);
+                point.specialization.data.hash(&mut hasher);
+            }
+        }
+        for set in layout.sets.iter() {
+            set.bindings.hash(&mut hasher);
+            for binding in set.layout.iter() {
+                binding.binding.hash(&mut hasher);
+                binding.ty.hash(&mut hasher);
+                binding.count.hash(&mut hasher);
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    /// Try to recreate a program from a cached binary.
+    fn load_cached_program(&self, cached: &n::CachedProgram) -> Option<glow::Program> {
+        let gl = &self.share.context;
+        unsafe {
+            let program = gl.create_program().unwrap();
+            gl.program_binary(
+                program,
+                &ext::ProgramBinary {
+                    buffer: cached.binary.clone(),
+                    format: cached.format,
+                },
+            );
+            if gl.get_program_link_status(program) {
+                log::info!("\tLoaded program {:?} from the pipeline cache", program);
+                self.bind_program_resources(program, &cached.name_binding_map);
+                Some(program)
+            } else {
+                // The driver may reject binaries from another driver version.
+                log::debug!("Cached program binary was rejected");
+                gl.delete_program(program);
+                None
+            }
+        }
+    }
+
     fn create_shader_program(
         &self,
         shaders: &[(naga::ShaderStage, Option<&pso::EntryPoint<B>>)],
         layout: &n::PipelineLayout,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<(glow::Program, n::SamplerBindMap), pso::CreationError> {
         let gl = &self.share.context;
+        let cache_key = match cache {
+            Some(_) if self.share.private_caps.program_binary => {
+                Self::program_cache_key(shaders, layout)
+            }
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (cache, cache_key) {
+            if let Some(cached) = cache.programs.lock().get(&key) {
+                if let Some(program) = self.load_cached_program(cached) {
+                    return Ok((program, cached.sampler_map));
+                }
+            }
+        }
+
         let program = unsafe { gl.create_program().unwrap() };
 
         let mut name_binding_map = FastHashMap::<String, (n::BindingRegister, u8)>::default();
@@ -195,6 +420,9 @@ impl Device {
         }
 
         unsafe {
+            if cache_key.is_some() {
+                gl.program_binary_retrievable_hint(program, true);
+            }
             gl.link_program(program);
         }
 
@@ -222,6 +450,33 @@ impl Device {
             log::warn!("\tLog: {}", log);
         }
 
+        self.bind_program_resources(program, &name_binding_map);
+
+        if let (Some(cache), Some(key)) = (cache, cache_key) {
+            if let Some(binary) = unsafe { gl.get_program_binary(program) } {
+                cache.programs.lock().insert(
+                    key,
+                    n::CachedProgram {
+                        format: binary.format,
+                        binary: binary.buffer,
+                        sampler_map,
+                        name_binding_map,
+                    },
+                );
+            }
+        }
+
+        Ok((program, sampler_map))
+    }
+
+    /// Assign texture units and block bindings by name, for contexts
+    /// that can't express them in the shader source.
+    fn bind_program_resources(
+        &self,
+        program: glow::Program,
+        name_binding_map: &FastHashMap<String, (n::BindingRegister, u8)>,
+    ) {
+        let gl = &self.share.context;
         if !self
             .share
             .legacy_features
@@ -233,7 +488,7 @@ impl Device {
             for (name, &(register, slot)) in name_binding_map.iter() {
                 log::trace!("Get binding {:?} from program {:?}", name, program);
                 match register {
-                    n::BindingRegister::Textures => unsafe {
+                    n::BindingRegister::Textures | n::BindingRegister::Images => unsafe {
                         let loc = gl.get_uniform_location(program, name).unwrap();
                         gl.uniform_1_i32(Some(&loc), slot as _);
                     },
@@ -248,8 +503,34 @@ impl Device {
                 }
             }
         }
+    }
+
+    /// Collect the uniforms of a linked program that back push constants.
+    unsafe fn reflect_push_constants(&self, program: n::Program) -> Vec<n::UniformDesc> {
+        let gl = &self.share.context;
+        let count = gl.get_active_uniforms(program);
+
+        let mut uniforms = Vec::new();
+        let mut offset = 0;
+
+        for uniform in 0..count {
+            let glow::ActiveUniform { size, utype, name } =
+                gl.get_active_uniform(program, uniform).unwrap();
+
+            if let Some(location) = gl.get_uniform_location(program, &name) {
+                // Sampler2D won't show up in UniformLocation and the only other uniforms
+                // should be push constants
+                uniforms.push(n::UniformDesc {
+                    location: Starc::new(location),
+                    offset,
+                    utype,
+                });
+
+                offset += size as u32;
+            }
+        }
 
-        Ok((program, sampler_map))
+        uniforms
     }
 
     fn _bind_target_compat(gl: &GlContainer, point: u32, attachment: u32, view: &n::ImageView) {
@@ -262,6 +543,7 @@ impl Device {
                 raw,
                 ref sub,
                 is_3d: false,
+                ..
             } => unsafe {
                 gl.bind_texture(target, Some(raw));
                 gl.framebuffer_texture_2d(
@@ -277,6 +559,7 @@ impl Device {
                 raw,
                 ref sub,
                 is_3d: true,
+                ..
             } => unsafe {
                 gl.bind_texture(target, Some(raw));
                 gl.framebuffer_texture_3d(
@@ -297,10 +580,10 @@ impl Device {
                 gl.framebuffer_renderbuffer(point, attachment, glow::RENDERBUFFER, Some(rb));
             },
             n::ImageView::Texture {
-                target: _,
+                target: glow::TEXTURE_2D,
                 raw,
                 ref sub,
-                is_3d: false,
+                ..
             } => unsafe {
                 gl.framebuffer_texture_2d(
                     point,
@@ -310,12 +593,18 @@ impl Device {
                     sub.level_start as _,
                 );
             },
+            // Array views covering several layers are attached as layered
+            // targets, letting the geometry stage select the layer. Such views
+            // are only created when they cover every layer of the texture.
             n::ImageView::Texture {
-                target: _,
                 raw,
                 ref sub,
                 is_3d: true,
-            } => unsafe {
+                ..
+            } if sub.layer_count.map_or(false, |count| count > 1) => unsafe {
+                gl.framebuffer_texture(point, attachment, Some(raw), sub.level_start as _);
+            },
+            n::ImageView::Texture { raw, ref sub, .. } => unsafe {
                 gl.framebuffer_texture_layer(
                     point,
                     attachment,
@@ -423,6 +712,13 @@ impl Device {
             layout,
             nb_map,
         );
+        self.remap_binding(
+            ast,
+            &res.storage_images,
+            n::BindingRegister::Images,
+            layout,
+            nb_map,
+        );
         self.remap_binding(
             ast,
             &res.uniform_buffers,
@@ -554,15 +850,23 @@ impl Device {
         }
 
         for (name, mapping) in reflection_info.texture_mapping {
-            let texture_linear_index = match module.global_variables[mapping.texture].binding {
+            let var = &module.global_variables[mapping.texture];
+            let texture_linear_index = match var.binding {
                 Some(ref br) => {
                     context.layout.sets[br.group as usize].bindings[br.binding as usize]
                 }
                 ref other => panic!("Unexpected texture binding {:?}", other),
             };
+            let register = match module.types[var.ty].inner {
+                naga::TypeInner::Image {
+                    class: naga::ImageClass::Storage(_),
+                    ..
+                } => n::BindingRegister::Images,
+                _ => n::BindingRegister::Textures,
+            };
             context
                 .name_binding_map
-                .insert(name, (n::BindingRegister::Textures, texture_linear_index));
+                .insert(name, (register, texture_linear_index));
             if let Some(sampler_handle) = mapping.sampler {
                 let sampler_linear_index = match module.global_variables[sampler_handle].binding {
                     Some(ref br) => {
@@ -653,8 +957,15 @@ impl Device {
             entry_point: ep.entry.to_string(),
         };
 
-        #[cfg_attr(not(feature = "cross"), allow(unused_mut))]
+        // Naga can't apply specialization constants, so specialized entry
+        // points go through GL_ARB_gl_spirv or SPIRV-Cross, and fail if
+        // neither is available rather than silently ignoring the constants.
+        let is_specialized = !ep.specialization.constants.is_empty();
         let mut result = match ep.module.naga {
+            Ok(_) if is_specialized => Err(d::ShaderError::CompilationFailed(format!(
+                "Specialization constants of entry point {} are not supported by Naga",
+                ep.entry
+            ))),
             Ok(ref shader) => Self::compile_shader_library_naga(
                 &self.share.context,
                 shader,
@@ -663,10 +974,14 @@ impl Device {
             ),
             Err(ref e) => Err(d::ShaderError::CompilationFailed(e.clone())),
         };
+        if result.is_err() && self.share.private_caps.gl_spirv && !ep.module.spv.is_empty() {
+            result = self.create_shader_module_spirv(ep, stage, context.layout);
+        }
         #[cfg(feature = "cross")]
         if result.is_err() {
             let mut ast = self.parse_spirv_cross(&ep.module.spv).unwrap();
-            auxil::spirv_cross_specialize_ast(&mut ast, &ep.specialization).unwrap();
+            auxil::spirv_cross_specialize_ast(&mut ast, &ep.specialization)
+                .map_err(d::ShaderError::CompilationFailed)?;
             self.remap_bindings(&mut ast, context.layout, context.name_binding_map);
             self.combine_separate_images_and_samplers(&mut ast, context.reborrow());
             self.set_push_const_layout(&mut ast).unwrap();
@@ -773,10 +1088,14 @@ impl d::Device<B> for Device {
                 let mut map_flags = 0;
 
                 if is_cpu_visible_memory {
-                    map_flags |= glow::MAP_WRITE_BIT | glow::MAP_FLUSH_EXPLICIT_BIT;
+                    map_flags |= glow::MAP_WRITE_BIT;
                     if is_readable_memory {
                         map_flags |= glow::MAP_READ_BIT;
                     }
+                    // Coherent mappings stay valid without explicit flushes.
+                    if !is_coherent_memory {
+                        map_flags |= glow::MAP_FLUSH_EXPLICIT_BIT;
+                    }
                 }
 
                 if self.share.private_caps.buffer_storage {
@@ -863,6 +1182,7 @@ impl d::Device<B> for Device {
             fbo,
             limits,
             memory: Arc::new(Mutex::new(memory)),
+            features: self.features,
             legacy_features: self.share.legacy_features,
         })
     }
@@ -893,10 +1213,12 @@ impl d::Device<B> for Device {
                 let color_attachments = subpass.colors.iter().map(|&(index, _)| index).collect();
 
                 let depth_stencil = subpass.depth_stencil.map(|ds| ds.0);
+                let input_attachments = subpass.inputs.iter().map(|&(index, _)| index).collect();
 
                 n::SubpassDesc {
                     color_attachments,
                     depth_stencil,
+                    input_attachments,
                 }
             })
             .collect();
@@ -919,6 +1241,7 @@ impl d::Device<B> for Device {
         let mut sets = Vec::new();
         let mut num_samplers = 0usize;
         let mut num_textures = 0usize;
+        let mut num_images = 0usize;
         let mut num_uniform_buffers = 0usize;
         let mut num_storage_buffers = 0usize;
 
@@ -931,9 +1254,20 @@ impl d::Device<B> for Device {
                 assert!(!binding.immutable_samplers); //TODO
                 let counter = match binding.ty {
                     pso::DescriptorType::Sampler => &mut num_samplers,
+                    pso::DescriptorType::Image {
+                        ty: pso::ImageDescriptorType::Storage { .. },
+                    } => &mut num_images,
                     pso::DescriptorType::InputAttachment | pso::DescriptorType::Image { .. } => {
                         &mut num_textures
                     }
+                    // Texel buffers are bound as buffer textures.
+                    pso::DescriptorType::Buffer {
+                        ty,
+                        format: pso::BufferDescriptorFormat::Texel,
+                    } => match ty {
+                        pso::BufferDescriptorType::Uniform => &mut num_textures,
+                        pso::BufferDescriptorType::Storage { .. } => &mut num_images,
+                    },
                     pso::DescriptorType::Buffer {
                         ty,
                         format: _, //TODO
@@ -956,32 +1290,55 @@ impl d::Device<B> for Device {
         Ok(n::PipelineLayout { sets })
     }
 
-    unsafe fn create_pipeline_cache(&self, _data: Option<&[u8]>) -> Result<(), d::OutOfMemory> {
-        Ok(())
+    unsafe fn create_pipeline_cache(
+        &self,
+        data: Option<&[u8]>,
+    ) -> Result<n::PipelineCache, d::OutOfMemory> {
+        Ok(match data {
+            Some(data) if !data.is_empty() => n::PipelineCache::from_data(data),
+            _ => n::PipelineCache::default(),
+        })
     }
 
-    unsafe fn get_pipeline_cache_data(&self, _cache: &()) -> Result<Vec<u8>, d::OutOfMemory> {
-        //empty
-        Ok(Vec::new())
+    unsafe fn get_pipeline_cache_data(
+        &self,
+        cache: &n::PipelineCache,
+    ) -> Result<Vec<u8>, d::OutOfMemory> {
+        Ok(cache.to_data())
     }
 
-    unsafe fn destroy_pipeline_cache(&self, _: ()) {
+    unsafe fn destroy_pipeline_cache(&self, _: n::PipelineCache) {
         //empty
     }
 
-    unsafe fn merge_pipeline_caches<'a, I>(&self, _: &mut (), _: I) -> Result<(), d::OutOfMemory>
+    unsafe fn merge_pipeline_caches<'a, I>(
+        &self,
+        target: &mut n::PipelineCache,
+        sources: I,
+    ) -> Result<(), d::OutOfMemory>
     where
-        I: Iterator<Item = &'a ()>,
+        I: Iterator<Item = &'a n::PipelineCache>,
     {
-        //empty
+        let programs = target.programs.get_mut();
+        for source in sources {
+            for (&key, program) in source.programs.lock().iter() {
+                programs.entry(key).or_insert_with(|| program.clone());
+            }
+        }
         Ok(())
     }
 
     unsafe fn create_graphics_pipeline<'a>(
         &self,
         desc: &pso::GraphicsPipelineDesc<'a, B>,
-        _cache: Option<&()>,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<n::GraphicsPipeline, pso::CreationError> {
+        // `Features::LOGIC_OP` is never exposed, since GLES has no logic ops at all.
+        if desc.blender.logic_op.is_some() {
+            log::error!("Logic ops are not supported");
+            return Err(pso::CreationError::UnsupportedPipeline);
+        }
+
         let (vertex_buffers, desc_attributes, input_assembler, vs) = match desc.primitive_assembler
         {
             pso::PrimitiveAssemblerDesc::Vertex {
@@ -1015,48 +1372,28 @@ impl d::Device<B> for Device {
             (naga::ShaderStage::Vertex, Some(vs)),
             (naga::ShaderStage::Fragment, desc.fragment.as_ref()),
         ];
-        let (program, sampler_map) = self.create_shader_program(&shaders[..], &desc.layout)?;
+        let (program, sampler_map) =
+            self.create_shader_program(&shaders[..], &desc.layout, cache)?;
 
         let patch_size = match input_assembler.primitive {
             pso::Primitive::PatchList(size) => Some(size as _),
             _ => None,
         };
 
-        let mut uniforms = Vec::new();
-        {
-            let gl = &self.share.context;
-            let count = gl.get_active_uniforms(program);
-
-            let mut offset = 0;
-
-            for uniform in 0..count {
-                let glow::ActiveUniform { size, utype, name } =
-                    gl.get_active_uniform(program, uniform).unwrap();
-
-                if let Some(location) = gl.get_uniform_location(program, &name) {
-                    // Sampler2D won't show up in UniformLocation and the only other uniforms
-                    // should be push constants
-                    uniforms.push(n::UniformDesc {
-                        location: Starc::new(location),
-                        offset,
-                        utype,
-                    });
-
-                    offset += size as u32;
-                }
-            }
-        }
+        let uniforms = self.reflect_push_constants(program);
+        let is_embedded = self.share.info.version.is_embedded;
 
         Ok(n::GraphicsPipeline {
             program,
             primitive: conv::input_assember_to_gl_primitive(input_assembler),
             patch_size,
+            primitive_restart: input_assembler.restart_index.is_some(),
             blend_targets: desc.blender.targets.clone(),
             vertex_buffers,
             attributes: desc_attributes
                 .iter()
                 .map(|&a| {
-                    let fd = conv::describe_format(a.element.format).unwrap();
+                    let fd = conv::describe_format(a.element.format, is_embedded).unwrap();
                     n::AttributeDesc {
                         location: a.location,
                         offset: a.element.offset,
@@ -1069,6 +1406,7 @@ impl d::Device<B> for Device {
                 .collect(),
             uniforms,
             rasterizer: desc.rasterizer,
+            multisampling: desc.multisampling.clone(),
             depth: desc.depth_stencil.depth,
             baked_states: desc.baked_states.clone(),
             sampler_map,
@@ -1078,15 +1416,17 @@ impl d::Device<B> for Device {
     unsafe fn create_compute_pipeline<'a>(
         &self,
         desc: &pso::ComputePipelineDesc<'a, B>,
-        _cache: Option<&()>,
+        cache: Option<&n::PipelineCache>,
     ) -> Result<n::ComputePipeline, pso::CreationError> {
         if self.share.public_caps.limits.max_compute_work_group_count[0] == 0 {
             return Err(pso::CreationError::UnsupportedPipeline);
         }
         let shader = (naga::ShaderStage::Compute, Some(&desc.shader));
-        let (program, sampler_map) = self.create_shader_program(&[shader], &desc.layout)?;
+        let (program, sampler_map) = self.create_shader_program(&[shader], &desc.layout, cache)?;
+        let uniforms = self.reflect_push_constants(program);
         Ok(n::ComputePipeline {
             program,
+            uniforms,
             sampler_map,
         })
     }
@@ -1181,7 +1521,6 @@ impl d::Device<B> for Device {
         raw_data: &[u32],
     ) -> Result<n::ShaderModule, d::ShaderError> {
         Ok(n::ShaderModule {
-            #[cfg(feature = "cross")]
             spv: raw_data.to_vec(),
             naga: if cfg!(feature = "cross") {
                 Err("Cross is enabled".into())
@@ -1223,6 +1562,8 @@ impl d::Device<B> for Device {
                     return Err((d::ShaderError::CompilationFailed(format!("{}", e)), shader))
                 }
             },
+            #[cfg(not(feature = "cross"))]
+            spv: Vec::new(),
             naga: Ok(shader),
         })
     }
@@ -1274,12 +1615,16 @@ impl d::Device<B> for Device {
             return Err(buffer::CreationError::UnsupportedUsage(usage));
         }
 
-        Ok(n::Buffer::Unbound { size, usage })
+        Ok(n::Buffer::Unbound {
+            size,
+            usage,
+            name: None,
+        })
     }
 
     unsafe fn get_buffer_requirements(&self, buffer: &n::Buffer) -> memory::Requirements {
         let (size, usage) = match *buffer {
-            n::Buffer::Unbound { size, usage } => (size, usage),
+            n::Buffer::Unbound { size, usage, .. } => (size, usage),
             n::Buffer::Bound { .. } => panic!("Unexpected Buffer::Bound"),
         };
 
@@ -1298,13 +1643,19 @@ impl d::Device<B> for Device {
         offset: u64,
         buffer: &mut n::Buffer,
     ) -> Result<(), d::BindError> {
-        let size = match *buffer {
-            n::Buffer::Unbound { size, .. } => size,
+        let (size, name) = match *buffer {
+            n::Buffer::Unbound { size, ref name, .. } => (size, name.as_ref()),
             n::Buffer::Bound { .. } => panic!("Unexpected Buffer::Bound"),
         };
 
         match memory.buffer {
             Some((raw, target)) => {
+                let gl = &self.share.context;
+                if let Some(name) = name {
+                    if gl.supports_debug() {
+                        gl.object_label(glow::BUFFER, raw, Some(name));
+                    }
+                }
                 *buffer = n::Buffer::Bound {
                     buffer: raw,
                     range: offset..offset + size,
@@ -1393,7 +1744,7 @@ impl d::Device<B> for Device {
                 let ptr = mem.emulate_map_allocation.unwrap();
                 let slice = slice::from_raw_parts_mut(ptr.offset(offset as isize), size as usize);
                 gl.buffer_sub_data_u8_slice(target, offset as i32, slice);
-            } else {
+            } else if mem.map_flags & glow::MAP_FLUSH_EXPLICIT_BIT != 0 {
                 gl.flush_mapped_buffer_range(target, offset as i32, size as i32);
             }
             gl.bind_buffer(target, None);
@@ -1421,14 +1772,17 @@ impl d::Device<B> for Device {
             let offset = segment.offset;
             let size = segment.size.unwrap_or(mem.size - segment.offset);
 
+            // Persistent mappings observe device writes once the submission
+            // has completed, since `submit` issues a client mapped buffer
+            // barrier before its fence. Only the emulated mapping needs a refresh.
+            // Note: `glInvalidateBufferSubData` must not be used here, it
+            // discards the buffer contents instead of making them visible.
             if self.share.private_caps.emulate_map {
                 let ptr = mem.emulate_map_allocation.unwrap();
                 let slice = slice::from_raw_parts_mut(ptr.offset(offset as isize), size as usize);
                 gl.get_buffer_sub_data(target, offset as i32, slice);
-            } else {
-                gl.invalidate_buffer_sub_data(target, offset as i32, size as i32);
-                gl.bind_buffer(target, None);
             }
+            gl.bind_buffer(target, None);
 
             if let Err(err) = self.share.check() {
                 panic!(
@@ -1443,11 +1797,48 @@ impl d::Device<B> for Device {
 
     unsafe fn create_buffer_view(
         &self,
-        _: &n::Buffer,
-        _: Option<Format>,
-        _: buffer::SubRange,
+        buffer: &n::Buffer,
+        format: Option<Format>,
+        sub: buffer::SubRange,
     ) -> Result<n::BufferView, buffer::ViewCreationError> {
-        unimplemented!()
+        if !self.share.private_caps.texture_buffer_range {
+            return Err(buffer::ViewCreationError::UnsupportedFormat(format));
+        }
+        let is_embedded = self.share.info.version.is_embedded;
+        let internal_format = match format.and_then(|f| conv::describe_format(f, is_embedded)) {
+            Some(description) => description.tex_internal,
+            None => return Err(buffer::ViewCreationError::UnsupportedFormat(format)),
+        };
+
+        let gl = &self.share.context;
+        let bounded_buffer = buffer.as_bound();
+        let range = crate::resolve_sub_range(&sub, bounded_buffer.range);
+        let limits = &self.share.public_caps.limits;
+        let alignment = limits.min_texel_buffer_offset_alignment;
+        if range.start % alignment != 0 {
+            log::error!(
+                "Texel buffer offset {} is not aligned to {}",
+                range.start,
+                alignment
+            );
+            return Err(buffer::ViewCreationError::UnsupportedFormat(format));
+        }
+
+        let raw = gl.create_texture().map_err(|_| d::OutOfMemory::Host)?;
+        gl.bind_texture(glow::TEXTURE_BUFFER, Some(raw));
+        gl.tex_buffer_range(
+            glow::TEXTURE_BUFFER,
+            internal_format,
+            Some(bounded_buffer.raw),
+            range.start as i32,
+            (range.end - range.start) as i32,
+        );
+        gl.bind_texture(glow::TEXTURE_BUFFER, None);
+
+        Ok(n::BufferView {
+            raw,
+            format: internal_format,
+        })
     }
 
     unsafe fn create_image(
@@ -1462,16 +1853,36 @@ impl d::Device<B> for Device {
     ) -> Result<n::Image, i::CreationError> {
         let gl = &self.share.context;
 
-        let desc = conv::describe_format(format).ok_or(i::CreationError::Format(format))?;
+        let desc = conv::describe_format(format, self.share.info.version.is_embedded)
+            .ok_or(i::CreationError::Format(format))?;
         let channel = format.base_format().1;
+        let surface_desc = format.base_format().0.desc();
+
+        // Compressed images can only be allocated without initial data
+        // through immutable texture storage.
+        if surface_desc.is_compressed() && !self.share.private_caps.image_storage {
+            return Err(i::CreationError::Format(format));
+        }
+
+        let is_3d = match kind {
+            i::Kind::D3(..) => true,
+            _ => false,
+        };
+
+        // Unsized internal formats (BGRA_EXT on GLES) can't use immutable storage.
+        let use_storage = self.share.private_caps.image_storage && desc.tex_internal != glow::BGRA;
 
         let mut pixel_count: u64 = 0;
-        let image = if num_levels > 1 || usage.intersects(i::Usage::STORAGE | i::Usage::SAMPLED) {
+        let image = if num_levels > 1
+            || usage.intersects(i::Usage::STORAGE | i::Usage::SAMPLED)
+            || surface_desc.is_compressed()
+            || is_3d
+        {
             let name = gl.create_texture().unwrap();
             let target = match kind {
                 i::Kind::D2(w, h, 1, 1) => {
                     gl.bind_texture(glow::TEXTURE_2D, Some(name));
-                    if self.share.private_caps.image_storage {
+                    if use_storage {
                         gl.tex_storage_2d(
                             glow::TEXTURE_2D,
                             num_levels as _,
@@ -1524,7 +1935,7 @@ impl d::Device<B> for Device {
                 }
                 i::Kind::D2(w, h, l, 1) => {
                     gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(name));
-                    if self.share.private_caps.image_storage {
+                    if use_storage {
                         gl.tex_storage_3d(
                             glow::TEXTURE_2D_ARRAY,
                             num_levels as _,
@@ -1577,12 +1988,69 @@ impl d::Device<B> for Device {
                     };
                     glow::TEXTURE_2D_ARRAY
                 }
+                i::Kind::D3(w, h, d) => {
+                    gl.bind_texture(glow::TEXTURE_3D, Some(name));
+                    if use_storage {
+                        gl.tex_storage_3d(
+                            glow::TEXTURE_3D,
+                            num_levels as _,
+                            desc.tex_internal,
+                            w as _,
+                            h as _,
+                            d as _,
+                        );
+                    } else {
+                        gl.tex_parameter_i32(
+                            glow::TEXTURE_3D,
+                            glow::TEXTURE_MAX_LEVEL,
+                            (num_levels - 1) as _,
+                        );
+                    }
+                    let (mut w, mut h, mut d) = (w, h, d);
+                    for i in 0..num_levels {
+                        if !use_storage {
+                            gl.tex_image_3d(
+                                glow::TEXTURE_3D,
+                                i as _,
+                                desc.tex_internal as i32,
+                                w as _,
+                                h as _,
+                                d as _,
+                                0,
+                                desc.tex_external,
+                                desc.data_type,
+                                None,
+                            );
+                        }
+                        pixel_count += (w * h) as u64 * d as u64;
+                        w = std::cmp::max(w / 2, 1);
+                        h = std::cmp::max(h / 2, 1);
+                        d = std::cmp::max(d / 2, 1);
+                    }
+                    match channel {
+                        ChannelType::Uint | ChannelType::Sint => {
+                            gl.tex_parameter_i32(
+                                glow::TEXTURE_3D,
+                                glow::TEXTURE_MIN_FILTER,
+                                glow::NEAREST as _,
+                            );
+                            gl.tex_parameter_i32(
+                                glow::TEXTURE_3D,
+                                glow::TEXTURE_MAG_FILTER,
+                                glow::NEAREST as _,
+                            );
+                        }
+                        _ => {}
+                    };
+                    glow::TEXTURE_3D
+                }
                 _ => unimplemented!(),
             };
             n::ImageType::Texture {
                 target,
                 raw: name,
                 format: desc.tex_external,
+                internal_format: desc.tex_internal,
                 pixel_type: desc.data_type,
                 layer_count: kind.num_layers(),
                 level_count: num_levels,
@@ -1610,12 +2078,13 @@ impl d::Device<B> for Device {
             n::ImageType::Renderbuffer {
                 raw: name,
                 format: desc.tex_external,
+                pixel_type: desc.data_type,
             }
         };
 
-        let surface_desc = format.base_format().0.desc();
-        let bytes_per_texel = surface_desc.bits / 8;
-        let size = pixel_count as u64 * bytes_per_texel as u64;
+        let texels_per_block = surface_desc.dim.0 as u64 * surface_desc.dim.1 as u64;
+        let size = (pixel_count * surface_desc.bits as u64 + texels_per_block * 8 - 1)
+            / (texels_per_block * 8);
         let type_mask = self.share.image_memory_type_mask();
 
         if let Err(err) = self.share.check() {
@@ -1679,7 +2148,7 @@ impl d::Device<B> for Device {
         kind: i::ViewKind,
         view_format: Format,
         swizzle: Swizzle,
-        _usage: i::Usage,
+        usage: i::Usage,
         range: i::SubresourceRange,
     ) -> Result<n::ImageView, i::ViewCreationError> {
         assert_eq!(swizzle, Swizzle::NO);
@@ -1701,13 +2170,15 @@ impl d::Device<B> for Device {
                 target,
                 raw,
                 format,
+                internal_format,
                 ..
             } => {
                 let is_3d = match kind {
                     i::ViewKind::D1 | i::ViewKind::D2 => false,
                     _ => true,
                 };
-                match conv::describe_format(view_format) {
+                let is_embedded = self.share.info.version.is_embedded;
+                let view_internal_format = match conv::describe_format(view_format, is_embedded) {
                     Some(description) => {
                         let raw_view_format = description.tex_external;
                         if format != raw_view_format {
@@ -1717,16 +2188,75 @@ impl d::Device<B> for Device {
                                 format
                             );
                         }
+                        description.tex_internal
                     }
                     None => {
                         log::warn!("View format {:?} is not supported", view_format);
+                        internal_format
                     }
+                };
+                // Resolve the counts, so the view can be bound without
+                // knowing about the image.
+                let sub = i::SubresourceRange {
+                    level_count: Some(range.resolve_level_count(image.num_levels)),
+                    layer_count: Some(range.resolve_layer_count(image.num_layers)),
+                    ..range
+                };
+                // Layered attachments always bind the whole texture, so a
+                // view of a subset of the layers can't be rendered to.
+                let attachment_usage =
+                    i::Usage::COLOR_ATTACHMENT | i::Usage::DEPTH_STENCIL_ATTACHMENT;
+                if is_3d
+                    && usage.intersects(attachment_usage)
+                    && sub.layer_count.map_or(false, |count| count > 1)
+                    && (!self.share.private_caps.layered_rendering
+                        || sub.layer_start != 0
+                        || sub.layer_count != Some(image.num_layers))
+                {
+                    return Err(i::ViewCreationError::Layer(i::LayerError::OutOfBounds));
                 }
+                // The sampled mip range is texture state (`TEXTURE_BASE_LEVEL` and
+                // `TEXTURE_MAX_LEVEL`), shared by every view of the texture. Views of a
+                // sub-range are sampled through a texture view aliasing that range instead.
+                // Without texture views, the layer range can't be sampled at all, and
+                // views of different mip ranges can't be bound at the same time.
+                let is_whole = sub.level_start == 0
+                    && sub.level_count == Some(image.num_levels)
+                    && sub.layer_start == 0
+                    && sub.layer_count == Some(image.num_layers);
+                // Only immutable textures can be aliased, see `create_image`.
+                let view = if usage.contains(i::Usage::SAMPLED)
+                    && !is_whole
+                    && self.share.private_caps.texture_view
+                    && self.share.private_caps.image_storage
+                    && internal_format != glow::BGRA
+                {
+                    let gl = &self.share.context;
+                    let view_target = conv::view_kind_to_target(kind, target);
+                    let name = gl.create_texture().map_err(|_| d::OutOfMemory::Device)?;
+                    gl.texture_view(
+                        name,
+                        view_target,
+                        raw,
+                        view_internal_format,
+                        sub.level_start as u32..(sub.level_start + sub.level_count.unwrap()) as u32,
+                        sub.layer_start as u32..(sub.layer_start + sub.layer_count.unwrap()) as u32,
+                    );
+                    Some((view_target, name))
+                } else if usage.contains(i::Usage::SAMPLED)
+                    && (sub.layer_start != 0 || sub.layer_count != Some(image.num_layers))
+                {
+                    return Err(i::ViewCreationError::Layer(i::LayerError::OutOfBounds));
+                } else {
+                    None
+                };
                 Ok(n::ImageView::Texture {
                     target,
                     raw,
+                    format: view_internal_format,
                     is_3d,
-                    sub: range,
+                    sub,
+                    view,
                 })
             }
         }
@@ -1799,15 +2329,11 @@ impl d::Device<B> for Device {
                     }
                 }
                 pso::Descriptor::CombinedImageSampler(view, _layout, sampler) => {
-                    match *view {
-                        n::ImageView::Texture { target, raw, .. } => op
-                            .set
-                            .bindings
-                            .push(n::DescSetBindings::Texture(raw, target)),
-                        n::ImageView::Renderbuffer { .. } => {
-                            panic!("Texture doesn't support shader binding")
-                        }
-                    }
+                    op.set.bindings.push(n::DescSetEntry {
+                        binding: binding_layout.binding,
+                        array_index: array_offset,
+                        value: sampled_binding(view),
+                    });
                     match *sampler {
                         n::FatSampler::Sampler(sampler) => n::DescSetBindings::Sampler(sampler),
                         n::FatSampler::Info(ref info) => {
@@ -1815,23 +2341,61 @@ impl d::Device<B> for Device {
                         }
                     }
                 }
-                pso::Descriptor::Image(view, _layout) => match *view {
-                    n::ImageView::Texture { target, raw, .. } => {
-                        n::DescSetBindings::Texture(raw, target)
-                    }
-                    n::ImageView::Renderbuffer { .. } => {
-                        panic!("Texture doesn't support shader binding")
-                    }
+                pso::Descriptor::Image(view, _layout) => match (view, binding_layout.ty) {
+                    (
+                        &n::ImageView::Texture {
+                            raw,
+                            format,
+                            is_3d,
+                            ref sub,
+                            ..
+                        },
+                        pso::DescriptorType::Image {
+                            ty: pso::ImageDescriptorType::Storage { read_only },
+                        },
+                    ) => n::DescSetBindings::Image {
+                        texture: raw,
+                        level: sub.level_start,
+                        layer: match is_3d {
+                            true => None,
+                            false => Some(sub.layer_start),
+                        },
+                        access: match read_only {
+                            true => glow::READ_ONLY,
+                            false => glow::READ_WRITE,
+                        },
+                        format,
+                    },
+                    (view, _) => sampled_binding(view),
                 },
                 pso::Descriptor::Sampler(sampler) => match *sampler {
                     n::FatSampler::Sampler(sampler) => n::DescSetBindings::Sampler(sampler),
                     n::FatSampler::Info(ref info) => n::DescSetBindings::SamplerDesc(info.clone()),
                 },
-                pso::Descriptor::TexelBuffer(_view) => unimplemented!(),
+                pso::Descriptor::TexelBuffer(view) => match binding_layout.ty {
+                    pso::DescriptorType::Buffer {
+                        ty: pso::BufferDescriptorType::Storage { read_only },
+                        ..
+                    } => n::DescSetBindings::Image {
+                        texture: view.raw,
+                        level: 0,
+                        layer: None,
+                        access: match read_only {
+                            true => glow::READ_ONLY,
+                            false => glow::READ_WRITE,
+                        },
+                        format: view.format,
+                    },
+                    _ => n::DescSetBindings::Texture(view.raw, glow::TEXTURE_BUFFER, 0..1),
+                },
             };
 
             //TODO: overwrite instead of pushing on top
-            op.set.bindings.push(binding);
+            op.set.bindings.push(n::DescSetEntry {
+                binding: binding_layout.binding,
+                array_index: array_offset,
+                value: binding,
+            });
 
             array_offset += 1;
             if array_offset == binding_layout.count {
@@ -1846,18 +2410,24 @@ impl d::Device<B> for Device {
             panic!("copying within same descriptor set is not currently supported");
         }
 
-        // TODO: add support for array bindings when the OpenGL backend gets them
-        let src_start = op.src_binding as usize;
-        let src_end = src_start + op.count;
-        assert!(src_end <= op.src_set.bindings.len());
-
-        let src_slice = &op.src_set.bindings[src_start..src_end];
-
-        let dst_start = op.dst_binding as usize;
-        let dst_end = dst_start + op.count;
-        assert!(dst_end <= op.dst_set.bindings.len());
-
-        op.dst_set.bindings[dst_start..dst_end].clone_from_slice(src_slice);
+        let src_positions =
+            Self::descriptor_positions(&op.src_set.layout, op.src_binding, op.src_array_offset);
+        let dst_positions =
+            Self::descriptor_positions(&op.dst_set.layout, op.dst_binding, op.dst_array_offset);
+        for (src, dst) in src_positions.zip(dst_positions).take(op.count) {
+            let copies = op
+                .src_set
+                .bindings
+                .iter()
+                .filter(|entry| (entry.binding, entry.array_index) == src)
+                .map(|entry| n::DescSetEntry {
+                    binding: dst.0,
+                    array_index: dst.1,
+                    value: entry.value.clone(),
+                })
+                .collect::<Vec<_>>();
+            op.dst_set.bindings.extend(copies);
+        }
     }
 
     fn create_semaphore(&self) -> Result<n::Semaphore, d::OutOfMemory> {
@@ -1891,20 +2461,31 @@ impl d::Device<B> for Device {
                 Ok(signaled)
             }
             n::Fence::Pending(sync) => {
-                // TODO: Could `wait_sync` be used here instead?
-                match gl.client_wait_sync(sync, glow::SYNC_FLUSH_COMMANDS_BIT, timeout_ns as i32) {
-                    glow::TIMEOUT_EXPIRED => Ok(false),
-                    glow::WAIT_FAILED => {
-                        if let Err(err) = self.share.check() {
-                            log::error!("Error when waiting on fence: {:?}", err);
+                // The timeout of `client_wait_sync` is limited to 32 bits,
+                // so longer waits are split into several calls.
+                let mut remaining_ns = timeout_ns;
+                loop {
+                    let chunk_ns = remaining_ns.min(i32::MAX as u64);
+                    // TODO: Could `wait_sync` be used here instead?
+                    match gl.client_wait_sync(sync, glow::SYNC_FLUSH_COMMANDS_BIT, chunk_ns as i32)
+                    {
+                        glow::TIMEOUT_EXPIRED if remaining_ns > chunk_ns => {
+                            remaining_ns -= chunk_ns;
                         }
-                        Ok(false)
-                    }
-                    glow::CONDITION_SATISFIED | glow::ALREADY_SIGNALED => {
-                        //fence.0.set(n::Fence::Idle { signaled: true });
-                        Ok(true)
+                        glow::TIMEOUT_EXPIRED => return Ok(false),
+                        glow::WAIT_FAILED => {
+                            self.share.check_context_lost()?;
+                            if let Err(err) = self.share.check() {
+                                log::error!("Error when waiting on fence: {:?}", err);
+                            }
+                            return Ok(false);
+                        }
+                        glow::CONDITION_SATISFIED | glow::ALREADY_SIGNALED => {
+                            //fence.0.set(n::Fence::Idle { signaled: true });
+                            return Ok(true);
+                        }
+                        _ => unreachable!(),
                     }
-                    _ => unreachable!(),
                 }
             }
         }
@@ -1958,6 +2539,7 @@ impl d::Device<B> for Device {
     }
 
     unsafe fn get_fence_status(&self, fence: &n::Fence) -> Result<bool, d::DeviceLost> {
+        self.share.check_context_lost()?;
         Ok(match *fence {
             n::Fence::Idle { signaled } => signaled,
             n::Fence::Pending(sync) => self.share.context.get_sync_status(sync) == glow::SIGNALED,
@@ -2037,8 +2619,8 @@ impl d::Device<B> for Device {
         // Nothing to do
     }
 
-    unsafe fn destroy_buffer_view(&self, _: n::BufferView) {
-        // Nothing to do
+    unsafe fn destroy_buffer_view(&self, view: n::BufferView) {
+        self.share.context.delete_texture(view.raw);
     }
 
     unsafe fn destroy_image(&self, image: n::Image) {
@@ -2049,8 +2631,14 @@ impl d::Device<B> for Device {
         }
     }
 
-    unsafe fn destroy_image_view(&self, _image_view: n::ImageView) {
-        // Nothing to do
+    unsafe fn destroy_image_view(&self, image_view: n::ImageView) {
+        if let n::ImageView::Texture {
+            view: Some((_, texture)),
+            ..
+        } = image_view
+        {
+            self.share.context.delete_texture(texture);
+        }
     }
 
     unsafe fn destroy_sampler(&self, sampler: n::FatSampler) {
@@ -2086,19 +2674,41 @@ impl d::Device<B> for Device {
         unimplemented!()
     }
 
-    fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), d::WaitError> {
         unsafe {
             self.share.context.finish();
         }
+        self.share.check_context_lost()?;
         Ok(())
     }
 
-    unsafe fn set_image_name(&self, _image: &mut n::Image, _name: &str) {
-        // TODO
+    unsafe fn set_image_name(&self, image: &mut n::Image, name: &str) {
+        let gl = &self.share.context;
+        if !gl.supports_debug() {
+            return;
+        }
+        match image.object_type {
+            n::ImageType::Renderbuffer { raw, .. } => {
+                gl.object_label(glow::RENDERBUFFER, raw, Some(name))
+            }
+            n::ImageType::Texture { raw, .. } => gl.object_label(glow::TEXTURE, raw, Some(name)),
+        }
     }
 
-    unsafe fn set_buffer_name(&self, _buffer: &mut n::Buffer, _name: &str) {
-        // TODO
+    unsafe fn set_buffer_name(&self, buffer: &mut n::Buffer, name: &str) {
+        let gl = &self.share.context;
+        if !gl.supports_debug() {
+            return;
+        }
+        match *buffer {
+            // Unbound buffers don't have a GL object yet, the label is
+            // attached in `bind_buffer_memory`.
+            n::Buffer::Unbound {
+                name: ref mut buffer_name,
+                ..
+            } => *buffer_name = Some(name.to_string()),
+            n::Buffer::Bound { buffer, .. } => gl.object_label(glow::BUFFER, buffer, Some(name)),
+        }
     }
 
     unsafe fn set_command_buffer_name(
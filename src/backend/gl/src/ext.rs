@@ -0,0 +1,604 @@
+//! Entry points that `glow` doesn't expose, loaded directly from the driver.
+//!
+//! They are only called when the matching private capability is detected,
+//! so a missing entry point is a driver bug.
+
+use crate::GlContainer;
+use std::{
+    ffi::CString,
+    mem,
+    ops::Range,
+    os::raw::{c_char, c_void},
+    ptr,
+};
+
+type Enum = u32;
+type Int = i32;
+type Sizei = i32;
+type Uint = u32;
+type Intptr = isize;
+
+type MultiDrawArraysIndirect = unsafe extern "system" fn(Enum, *const c_void, Sizei, Sizei);
+type MultiDrawElementsIndirect = unsafe extern "system" fn(Enum, Enum, *const c_void, Sizei, Sizei);
+type MultiDrawArraysIndirectCount =
+    unsafe extern "system" fn(Enum, *const c_void, Intptr, Sizei, Sizei);
+type MultiDrawElementsIndirectCount =
+    unsafe extern "system" fn(Enum, Enum, *const c_void, Intptr, Sizei, Sizei);
+type DrawArraysIndirect = unsafe extern "system" fn(Enum, *const c_void);
+type DrawElementsIndirect = unsafe extern "system" fn(Enum, Enum, *const c_void);
+type CompressedTexSubImage2D =
+    unsafe extern "system" fn(Enum, Int, Int, Int, Sizei, Sizei, Enum, Sizei, *const c_void);
+type CompressedTexSubImage3D = unsafe extern "system" fn(
+    Enum,
+    Int,
+    Int,
+    Int,
+    Int,
+    Sizei,
+    Sizei,
+    Sizei,
+    Enum,
+    Sizei,
+    *const c_void,
+);
+type TextureSubImage2D =
+    unsafe extern "system" fn(Uint, Int, Int, Int, Sizei, Sizei, Enum, Enum, *const c_void);
+type TextureSubImage3D = unsafe extern "system" fn(
+    Uint,
+    Int,
+    Int,
+    Int,
+    Int,
+    Sizei,
+    Sizei,
+    Sizei,
+    Enum,
+    Enum,
+    *const c_void,
+);
+type CopyNamedBufferSubData = unsafe extern "system" fn(Uint, Uint, Intptr, Intptr, Intptr);
+type TexBufferRange = unsafe extern "system" fn(Enum, Enum, Uint, Intptr, Intptr);
+type TextureView = unsafe extern "system" fn(Uint, Enum, Uint, Enum, Uint, Uint, Uint, Uint);
+type GetInternalformativ = unsafe extern "system" fn(Enum, Enum, Enum, Sizei, *mut Int);
+type MinSampleShading = unsafe extern "system" fn(f32);
+type GetProgramiv = unsafe extern "system" fn(Uint, Enum, *mut Int);
+type ProgramParameteri = unsafe extern "system" fn(Uint, Enum, Int);
+type GetProgramBinary = unsafe extern "system" fn(Uint, Sizei, *mut Sizei, *mut Enum, *mut c_void);
+type ProgramBinaryFn = unsafe extern "system" fn(Uint, Enum, *const c_void, Sizei);
+type ShaderBinary = unsafe extern "system" fn(Sizei, *const Uint, Enum, *const c_void, Sizei);
+type SpecializeShader =
+    unsafe extern "system" fn(Uint, *const c_char, Uint, *const Uint, *const Uint);
+
+/// Linked program binary, as returned by `glGetProgramBinary`.
+#[derive(Clone, Debug)]
+pub(crate) struct ProgramBinary {
+    pub buffer: Vec<u8>,
+    pub format: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct ExtFns {
+    multi_draw_arrays_indirect: Option<MultiDrawArraysIndirect>,
+    multi_draw_elements_indirect: Option<MultiDrawElementsIndirect>,
+    multi_draw_arrays_indirect_count: Option<MultiDrawArraysIndirectCount>,
+    multi_draw_elements_indirect_count: Option<MultiDrawElementsIndirectCount>,
+    draw_arrays_indirect: Option<DrawArraysIndirect>,
+    draw_elements_indirect: Option<DrawElementsIndirect>,
+    compressed_tex_sub_image_2d: Option<CompressedTexSubImage2D>,
+    compressed_tex_sub_image_3d: Option<CompressedTexSubImage3D>,
+    texture_sub_image_2d: Option<TextureSubImage2D>,
+    texture_sub_image_3d: Option<TextureSubImage3D>,
+    copy_named_buffer_sub_data: Option<CopyNamedBufferSubData>,
+    tex_buffer_range: Option<TexBufferRange>,
+    texture_view: Option<TextureView>,
+    get_internalformativ: Option<GetInternalformativ>,
+    min_sample_shading: Option<MinSampleShading>,
+    get_programiv: Option<GetProgramiv>,
+    program_parameteri: Option<ProgramParameteri>,
+    get_program_binary: Option<GetProgramBinary>,
+    program_binary: Option<ProgramBinaryFn>,
+    shader_binary: Option<ShaderBinary>,
+    specialize_shader: Option<SpecializeShader>,
+}
+
+impl ExtFns {
+    /// Load the entry points, trying the extension suffixes when the core name is missing.
+    pub(crate) fn load<F>(mut loader: F) -> Self
+    where
+        F: FnMut(&str) -> *const c_void,
+    {
+        macro_rules! load {
+            ($($name:expr),+) => {{
+                let mut ptr = ptr::null();
+                for name in &[$($name),+] {
+                    ptr = loader(*name);
+                    if !ptr.is_null() {
+                        break;
+                    }
+                }
+                if ptr.is_null() {
+                    None
+                } else {
+                    Some(unsafe { mem::transmute(ptr) })
+                }
+            }};
+        }
+
+        ExtFns {
+            multi_draw_arrays_indirect: load!(
+                "glMultiDrawArraysIndirect",
+                "glMultiDrawArraysIndirectEXT"
+            ),
+            multi_draw_elements_indirect: load!(
+                "glMultiDrawElementsIndirect",
+                "glMultiDrawElementsIndirectEXT"
+            ),
+            multi_draw_arrays_indirect_count: load!(
+                "glMultiDrawArraysIndirectCount",
+                "glMultiDrawArraysIndirectCountARB"
+            ),
+            multi_draw_elements_indirect_count: load!(
+                "glMultiDrawElementsIndirectCount",
+                "glMultiDrawElementsIndirectCountARB"
+            ),
+            draw_arrays_indirect: load!("glDrawArraysIndirect"),
+            draw_elements_indirect: load!("glDrawElementsIndirect"),
+            compressed_tex_sub_image_2d: load!("glCompressedTexSubImage2D"),
+            compressed_tex_sub_image_3d: load!("glCompressedTexSubImage3D"),
+            texture_sub_image_2d: load!("glTextureSubImage2D"),
+            texture_sub_image_3d: load!("glTextureSubImage3D"),
+            copy_named_buffer_sub_data: load!("glCopyNamedBufferSubData"),
+            tex_buffer_range: load!("glTexBufferRange"),
+            texture_view: load!("glTextureView", "glTextureViewOES", "glTextureViewEXT"),
+            get_internalformativ: load!("glGetInternalformativ"),
+            min_sample_shading: load!(
+                "glMinSampleShading",
+                "glMinSampleShadingARB",
+                "glMinSampleShadingOES"
+            ),
+            get_programiv: load!("glGetProgramiv"),
+            program_parameteri: load!("glProgramParameteri"),
+            get_program_binary: load!("glGetProgramBinary", "glGetProgramBinaryOES"),
+            program_binary: load!("glProgramBinary", "glProgramBinaryOES"),
+            shader_binary: load!("glShaderBinary"),
+            specialize_shader: load!("glSpecializeShader", "glSpecializeShaderARB"),
+        }
+    }
+}
+
+/// Raw name of a GL object.
+trait RawName {
+    fn raw(self) -> Uint;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RawName for Uint {
+    fn raw(self) -> Uint {
+        self
+    }
+}
+
+// None of the entry points are loaded on WebGL, so the objects are never passed to them.
+#[cfg(target_arch = "wasm32")]
+macro_rules! impl_web_raw_name {
+    ($($ty:ty),*) => {$(
+        impl RawName for $ty {
+            fn raw(self) -> Uint {
+                unreachable!("WebGL objects have no raw names")
+            }
+        }
+    )*};
+}
+#[cfg(target_arch = "wasm32")]
+impl_web_raw_name!(glow::Shader, glow::Program, glow::Buffer, glow::Texture);
+
+fn loaded<T>(function: Option<T>, name: &str) -> T {
+    function.unwrap_or_else(|| panic!("{} is not loaded", name))
+}
+
+impl GlContainer {
+    pub(crate) unsafe fn multi_draw_arrays_indirect_offset(
+        &self,
+        mode: u32,
+        offset: i32,
+        draw_count: i32,
+        stride: i32,
+    ) {
+        let f = loaded(
+            self.ext.multi_draw_arrays_indirect,
+            "glMultiDrawArraysIndirect",
+        );
+        f(mode, offset as usize as *const _, draw_count, stride);
+    }
+
+    pub(crate) unsafe fn multi_draw_elements_indirect_offset(
+        &self,
+        mode: u32,
+        index_type: u32,
+        offset: i32,
+        draw_count: i32,
+        stride: i32,
+    ) {
+        let f = loaded(
+            self.ext.multi_draw_elements_indirect,
+            "glMultiDrawElementsIndirect",
+        );
+        f(
+            mode,
+            index_type,
+            offset as usize as *const _,
+            draw_count,
+            stride,
+        );
+    }
+
+    /// The draw count is read from `count_offset` in the bound `PARAMETER_BUFFER`.
+    pub(crate) unsafe fn multi_draw_arrays_indirect_count_offset(
+        &self,
+        mode: u32,
+        offset: i32,
+        count_offset: i32,
+        max_draw_count: i32,
+        stride: i32,
+    ) {
+        let f = loaded(
+            self.ext.multi_draw_arrays_indirect_count,
+            "glMultiDrawArraysIndirectCount",
+        );
+        f(
+            mode,
+            offset as usize as *const _,
+            count_offset as _,
+            max_draw_count,
+            stride,
+        );
+    }
+
+    /// The draw count is read from `count_offset` in the bound `PARAMETER_BUFFER`.
+    pub(crate) unsafe fn multi_draw_elements_indirect_count_offset(
+        &self,
+        mode: u32,
+        index_type: u32,
+        offset: i32,
+        count_offset: i32,
+        max_draw_count: i32,
+        stride: i32,
+    ) {
+        let f = loaded(
+            self.ext.multi_draw_elements_indirect_count,
+            "glMultiDrawElementsIndirectCount",
+        );
+        f(
+            mode,
+            index_type,
+            offset as usize as *const _,
+            count_offset as _,
+            max_draw_count,
+            stride,
+        );
+    }
+
+    pub(crate) unsafe fn draw_arrays_indirect_offset(&self, mode: u32, offset: i32) {
+        let f = loaded(self.ext.draw_arrays_indirect, "glDrawArraysIndirect");
+        f(mode, offset as usize as *const _);
+    }
+
+    pub(crate) unsafe fn draw_elements_indirect_offset(
+        &self,
+        mode: u32,
+        index_type: u32,
+        offset: i32,
+    ) {
+        let f = loaded(self.ext.draw_elements_indirect, "glDrawElementsIndirect");
+        f(mode, index_type, offset as usize as *const _);
+    }
+
+    /// Upload from `range` of the bound `PIXEL_UNPACK_BUFFER`.
+    pub(crate) unsafe fn compressed_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        range: Range<u32>,
+    ) {
+        let f = loaded(
+            self.ext.compressed_tex_sub_image_2d,
+            "glCompressedTexSubImage2D",
+        );
+        f(
+            target,
+            level,
+            x_offset,
+            y_offset,
+            width,
+            height,
+            format,
+            (range.end - range.start) as _,
+            range.start as usize as *const _,
+        );
+    }
+
+    /// Upload from `range` of the bound `PIXEL_UNPACK_BUFFER`.
+    pub(crate) unsafe fn compressed_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        range: Range<u32>,
+    ) {
+        let f = loaded(
+            self.ext.compressed_tex_sub_image_3d,
+            "glCompressedTexSubImage3D",
+        );
+        f(
+            target,
+            level,
+            x_offset,
+            y_offset,
+            z_offset,
+            width,
+            height,
+            depth,
+            format,
+            (range.end - range.start) as _,
+            range.start as usize as *const _,
+        );
+    }
+
+    pub(crate) unsafe fn texture_sub_image_2d(
+        &self,
+        texture: glow::Texture,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        ty: u32,
+        pixels: glow::PixelUnpackData,
+    ) {
+        let f = loaded(self.ext.texture_sub_image_2d, "glTextureSubImage2D");
+        f(
+            texture.raw(),
+            level,
+            x_offset,
+            y_offset,
+            width,
+            height,
+            format,
+            ty,
+            unpack_pointer(pixels),
+        );
+    }
+
+    pub(crate) unsafe fn texture_sub_image_3d(
+        &self,
+        texture: glow::Texture,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        ty: u32,
+        pixels: glow::PixelUnpackData,
+    ) {
+        let f = loaded(self.ext.texture_sub_image_3d, "glTextureSubImage3D");
+        f(
+            texture.raw(),
+            level,
+            x_offset,
+            y_offset,
+            z_offset,
+            width,
+            height,
+            depth,
+            format,
+            ty,
+            unpack_pointer(pixels),
+        );
+    }
+
+    pub(crate) unsafe fn copy_named_buffer_sub_data(
+        &self,
+        src_buffer: glow::Buffer,
+        dst_buffer: glow::Buffer,
+        src_offset: i32,
+        dst_offset: i32,
+        size: i32,
+    ) {
+        let f = loaded(
+            self.ext.copy_named_buffer_sub_data,
+            "glCopyNamedBufferSubData",
+        );
+        f(
+            src_buffer.raw(),
+            dst_buffer.raw(),
+            src_offset as _,
+            dst_offset as _,
+            size as _,
+        );
+    }
+
+    pub(crate) unsafe fn tex_buffer_range(
+        &self,
+        target: u32,
+        internal_format: u32,
+        buffer: Option<glow::Buffer>,
+        offset: i32,
+        size: i32,
+    ) {
+        let f = loaded(self.ext.tex_buffer_range, "glTexBufferRange");
+        f(
+            target,
+            internal_format,
+            buffer.map_or(0, RawName::raw),
+            offset as _,
+            size as _,
+        );
+    }
+
+    /// Makes `texture`, which must be a fresh texture name, alias a sub-range of `original`.
+    pub(crate) unsafe fn texture_view(
+        &self,
+        texture: glow::Texture,
+        target: u32,
+        original: glow::Texture,
+        internal_format: u32,
+        levels: Range<u32>,
+        layers: Range<u32>,
+    ) {
+        let f = loaded(self.ext.texture_view, "glTextureView");
+        f(
+            texture.raw(),
+            target,
+            original.raw(),
+            internal_format,
+            levels.start,
+            levels.end - levels.start,
+            layers.start,
+            layers.end - layers.start,
+        );
+    }
+
+    pub(crate) unsafe fn min_sample_shading(&self, value: f32) {
+        let f = loaded(self.ext.min_sample_shading, "glMinSampleShading");
+        f(value);
+    }
+
+    pub(crate) unsafe fn program_binary_retrievable_hint(
+        &self,
+        program: glow::Program,
+        value: bool,
+    ) {
+        let f = loaded(self.ext.program_parameteri, "glProgramParameteri");
+        f(
+            program.raw(),
+            glow::PROGRAM_BINARY_RETRIEVABLE_HINT,
+            value as _,
+        );
+    }
+
+    /// Sample counts supported by the internal format, in decreasing order.
+    pub(crate) unsafe fn get_internal_format_samples(
+        &self,
+        target: u32,
+        internal_format: u32,
+    ) -> Vec<i32> {
+        let f = loaded(self.ext.get_internalformativ, "glGetInternalformativ");
+        let mut count = 0;
+        f(
+            target,
+            internal_format,
+            glow::NUM_SAMPLE_COUNTS,
+            1,
+            &mut count,
+        );
+        let mut samples = vec![0; count.max(0) as usize];
+        if !samples.is_empty() {
+            f(
+                target,
+                internal_format,
+                glow::SAMPLES,
+                count,
+                samples.as_mut_ptr(),
+            );
+        }
+        samples
+    }
+
+    /// Returns `None` if the driver doesn't provide a binary for the program.
+    pub(crate) unsafe fn get_program_binary(
+        &self,
+        program: glow::Program,
+    ) -> Option<ProgramBinary> {
+        let get_programiv = loaded(self.ext.get_programiv, "glGetProgramiv");
+        let get_program_binary = loaded(self.ext.get_program_binary, "glGetProgramBinary");
+
+        let mut length = 0;
+        get_programiv(program.raw(), glow::PROGRAM_BINARY_LENGTH, &mut length);
+        if length <= 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; length as usize];
+        let mut format = 0;
+        get_program_binary(
+            program.raw(),
+            length,
+            &mut length,
+            &mut format,
+            buffer.as_mut_ptr() as *mut _,
+        );
+        buffer.truncate(length.max(0) as usize);
+        if buffer.is_empty() {
+            None
+        } else {
+            Some(ProgramBinary { buffer, format })
+        }
+    }
+
+    pub(crate) unsafe fn program_binary(&self, program: glow::Program, binary: &ProgramBinary) {
+        let f = loaded(self.ext.program_binary, "glProgramBinary");
+        f(
+            program.raw(),
+            binary.format,
+            binary.buffer.as_ptr() as *const _,
+            binary.buffer.len() as _,
+        );
+    }
+
+    pub(crate) unsafe fn shader_binary(
+        &self,
+        shaders: &[glow::Shader],
+        format: u32,
+        binary: &[u8],
+    ) {
+        let f = loaded(self.ext.shader_binary, "glShaderBinary");
+        let shaders = shaders.iter().map(|&s| s.raw()).collect::<Vec<_>>();
+        f(
+            shaders.len() as _,
+            shaders.as_ptr(),
+            format,
+            binary.as_ptr() as *const _,
+            binary.len() as _,
+        );
+    }
+
+    pub(crate) unsafe fn specialize_shader(
+        &self,
+        shader: glow::Shader,
+        entry_point: &str,
+        constant_ids: &[u32],
+        constant_values: &[u32],
+    ) {
+        assert_eq!(constant_ids.len(), constant_values.len());
+        let f = loaded(self.ext.specialize_shader, "glSpecializeShader");
+        let entry_point = CString::new(entry_point).unwrap();
+        f(
+            shader.raw(),
+            entry_point.as_ptr(),
+            constant_ids.len() as _,
+            constant_ids.as_ptr(),
+            constant_values.as_ptr(),
+        );
+    }
+}
+
+fn unpack_pointer(pixels: glow::PixelUnpackData) -> *const c_void {
+    match pixels {
+        glow::PixelUnpackData::BufferOffset(offset) => offset as usize as *const _,
+        glow::PixelUnpackData::Slice(data) => data.as_ptr() as *const _,
+    }
+}
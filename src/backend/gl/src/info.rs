@@ -1,6 +1,8 @@
 use crate::{Error, GlContainer, MAX_COLOR_ATTACHMENTS};
 use glow::HasContext;
-use hal::{DynamicStates, Features, Limits, PerformanceCaveats, PhysicalDeviceProperties};
+use hal::{
+    DescriptorLimits, DynamicStates, Features, Limits, PerformanceCaveats, PhysicalDeviceProperties,
+};
 use std::{collections::HashSet, fmt, str};
 
 /// A version number for a specific component of an OpenGL implementation
@@ -164,6 +166,25 @@ fn get_usize(gl: &GlContainer, name: u32) -> Result<usize, Error> {
         Ok(value as usize)
     }
 }
+fn get_usize_pair(gl: &GlContainer, name: u32) -> Result<[usize; 2], Error> {
+    let mut values = [0; 2];
+    unsafe { gl.get_parameter_i32_slice(name, &mut values) };
+    let err = Error::from_error_code(unsafe { gl.get_error() });
+    if err != Error::NoError {
+        Err(err)
+    } else {
+        Ok([values[0] as usize, values[1] as usize])
+    }
+}
+fn get_f32(gl: &GlContainer, name: u32) -> Result<f32, Error> {
+    let value = unsafe { gl.get_parameter_f32(name) };
+    let err = Error::from_error_code(unsafe { gl.get_error() });
+    if err != Error::NoError {
+        Err(err)
+    } else {
+        Ok(value)
+    }
+}
 fn get_u64(gl: &GlContainer, name: u32) -> Result<u64, Error> {
     let value = unsafe { gl.get_parameter_i32(name) };
     let err = Error::from_error_code(unsafe { gl.get_error() });
@@ -203,6 +224,8 @@ pub struct PrivateCaps {
     pub framebuffer: bool,
     /// FBO support to call `glFramebufferTexture`
     pub framebuffer_texture: bool,
+    /// Whole texture arrays can be attached as layered framebuffer attachments.
+    pub layered_rendering: bool,
     /// If true, then buffers used as ELEMENT_ARRAY_BUFFER may be created / initialized / used as
     /// other targets, if false they must not be mixed with other targets.
     pub index_buffer_role_change: bool,
@@ -229,6 +252,31 @@ pub struct PrivateCaps {
     pub get_tex_image: bool,
     /// Inserting memory barriers.
     pub memory_barrier: bool,
+    /// SPIR-V modules can be loaded directly (`GL_ARB_gl_spirv`).
+    pub gl_spirv: bool,
+    /// Buffer textures over a sub-range of a buffer (`glTexBufferRange`).
+    pub texture_buffer_range: bool,
+    /// Textures can alias a sub-range of another texture (`glTextureView`).
+    pub texture_view: bool,
+    /// Primitive restart with the maximum index value can be toggled.
+    /// It's always enabled in OpenGL ES 3.0.
+    pub primitive_restart_fixed_index: bool,
+    /// Linked programs can be retrieved and reloaded as binaries.
+    pub program_binary: bool,
+    /// Framebuffer attachments can be invalidated.
+    pub invalidate_framebuffer: bool,
+    /// Buffers and textures can be modified by name without binding them.
+    pub direct_state_access: bool,
+    /// Multiple indirect draws can be issued with a single call (`glMultiDraw*Indirect`).
+    pub multi_draw_indirect: bool,
+    /// The indirect draw count can be sourced from a buffer (`glMultiDraw*IndirectCount`).
+    pub indirect_parameters: bool,
+    /// Context loss can be queried (`glGetGraphicsResetStatus`).
+    pub robustness: bool,
+    /// Supported sample counts can be queried per format (`glGetInternalformativ`).
+    pub internal_format_query: bool,
+    /// Sample count mask of multisampled integer formats (`GL_MAX_INTEGER_SAMPLES`).
+    pub integer_sample_counts: u8,
 }
 
 /// OpenGL implementation information
@@ -276,6 +324,8 @@ bitflags::bitflags! {
         const EXPLICIT_LAYOUTS_IN_SHADER = 0x00002000;
         /// Support instanced input rate on attribute binding.
         const INSTANCED_ATTRIBUTE_BINDING = 0x00004000;
+        /// Support binding images for load/store operations in shaders.
+        const IMAGE_LOAD_STORE = 0x00008000;
     }
 }
 
@@ -596,16 +646,40 @@ pub(crate) fn query_all(
     let max_texture_size = get_usize(gl, glow::MAX_TEXTURE_SIZE).unwrap_or(64) as u32;
     let max_samples = get_usize(gl, glow::MAX_SAMPLES).unwrap_or(8);
     let max_samples_mask = (max_samples * 2 - 1) as u8;
+    // OpenGL ES 3.0 doesn't allow multisampled integer formats.
+    let max_integer_samples =
+        if info.is_supported(&[Core(3, 2), Es(3, 1), Ext("GL_ARB_texture_multisample")]) {
+            get_usize(gl, glow::MAX_INTEGER_SAMPLES).unwrap_or(1)
+        } else {
+            1
+        };
+    let integer_sample_counts = (max_integer_samples.max(1) * 2 - 1) as u8;
     let max_texel_elements = if crate::is_webgl() {
         0
     } else {
         get_usize(gl, glow::MAX_TEXTURE_BUFFER_SIZE).unwrap_or(0)
     };
+    let min_texel_buffer_offset_alignment = if max_texel_elements == 0 {
+        1
+    } else {
+        get_u64(gl, glow::TEXTURE_BUFFER_OFFSET_ALIGNMENT).unwrap_or(256)
+    };
     let min_storage_buffer_offset_alignment = if crate::is_webgl() {
         256
     } else {
         get_u64(gl, glow::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT).unwrap_or(256)
     };
+    let max_renderbuffer_size =
+        get_usize(gl, glow::MAX_RENDERBUFFER_SIZE).map_or(max_texture_size, |size| size as u32);
+    let max_viewport_dimensions = get_usize_pair(gl, glow::MAX_VIEWPORT_DIMS)
+        .map_or([max_renderbuffer_size; 2], |[w, h]| [w as u32, h as u32]);
+    let fragment_uniform_blocks =
+        get_usize(gl, glow::MAX_FRAGMENT_UNIFORM_BLOCKS).unwrap_or(0) as u32;
+    let combined_uniform_blocks =
+        get_usize(gl, glow::MAX_COMBINED_UNIFORM_BLOCKS).unwrap_or(0) as u32;
+    let fragment_texture_units = get_usize(gl, glow::MAX_TEXTURE_IMAGE_UNITS).unwrap_or(0) as u32;
+    let combined_texture_units =
+        get_usize(gl, glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS).unwrap_or(0) as u32;
 
     let mut limits = Limits {
         max_image_1d_size: max_texture_size,
@@ -614,26 +688,44 @@ pub(crate) fn query_all(
         max_image_cube_size: max_texture_size,
         max_image_array_layers: get_usize(gl, glow::MAX_ARRAY_TEXTURE_LAYERS).unwrap_or(1) as u16,
         max_texel_elements,
+        max_uniform_buffer_range: get_u64(gl, glow::MAX_UNIFORM_BLOCK_SIZE).unwrap_or(16384),
+        max_vertex_input_attributes: get_usize(gl, glow::MAX_VERTEX_ATTRIBS).unwrap_or(16),
         max_viewports: 1,
+        max_viewport_dimensions,
+        max_framebuffer_extent: hal::image::Extent {
+            width: max_renderbuffer_size,
+            height: max_renderbuffer_size,
+            depth: 1,
+        },
         optimal_buffer_copy_offset_alignment: 1,
         optimal_buffer_copy_pitch_alignment: 1,
-        min_texel_buffer_offset_alignment: 1,
+        min_texel_buffer_offset_alignment,
         min_uniform_buffer_offset_alignment: get_u64(gl, glow::UNIFORM_BUFFER_OFFSET_ALIGNMENT)
             .unwrap_or(1024),
         min_storage_buffer_offset_alignment,
         framebuffer_color_sample_counts: max_samples_mask,
+        framebuffer_depth_sample_counts: max_samples_mask,
+        framebuffer_stencil_sample_counts: max_samples_mask,
         non_coherent_atom_size: 1,
         max_color_attachments: get_usize(gl, glow::MAX_COLOR_ATTACHMENTS)
             .unwrap_or(1)
             .min(MAX_COLOR_ATTACHMENTS),
+        descriptor_limits: DescriptorLimits {
+            max_per_stage_descriptor_uniform_buffers: fragment_uniform_blocks,
+            max_descriptor_set_uniform_buffers: combined_uniform_blocks,
+            max_per_stage_descriptor_samplers: fragment_texture_units,
+            max_per_stage_descriptor_sampled_images: fragment_texture_units,
+            max_descriptor_set_samplers: combined_texture_units,
+            max_descriptor_set_sampled_images: combined_texture_units,
+            ..DescriptorLimits::default()
+        },
         ..Limits::default()
     };
 
     if info.is_supported(&[Core(4, 0), Ext("GL_ARB_tessellation_shader")]) {
         limits.max_patch_size = get_usize(gl, glow::MAX_PATCH_VERTICES).unwrap_or(0) as _;
     }
-    if info.is_supported(&[Core(4, 1)]) {
-        // TODO: extension
+    if info.is_supported(&[Core(4, 1), Ext("GL_ARB_viewport_array")]) {
         limits.max_viewports = get_usize(gl, glow::MAX_VIEWPORTS).unwrap_or(0);
     }
 
@@ -657,6 +749,37 @@ pub(crate) fn query_all(
     }
 
     let mut features = Features::NDC_Y_UP | Features::MUTABLE_COMPARISON_SAMPLER;
+    if limits.max_viewports > 1 {
+        features |= Features::MULTI_VIEWPORTS;
+    }
+    if !info.version.is_embedded {
+        features |= Features::NON_FILL_POLYGON_MODE;
+    }
+    if info.is_supported(&[
+        Core(4, 3),
+        Es(3, 1),
+        Ext("GL_ARB_shader_storage_buffer_object"),
+    ]) {
+        let vertex_blocks =
+            get_usize(gl, glow::MAX_VERTEX_SHADER_STORAGE_BLOCKS).unwrap_or(0) as u32;
+        let fragment_blocks =
+            get_usize(gl, glow::MAX_FRAGMENT_SHADER_STORAGE_BLOCKS).unwrap_or(0) as u32;
+        let combined_blocks =
+            get_usize(gl, glow::MAX_COMBINED_SHADER_STORAGE_BLOCKS).unwrap_or(0) as u32;
+        limits.max_storage_buffer_range =
+            get_u64(gl, glow::MAX_SHADER_STORAGE_BLOCK_SIZE).unwrap_or(0);
+        limits
+            .descriptor_limits
+            .max_per_stage_descriptor_storage_buffers = fragment_blocks;
+        limits.descriptor_limits.max_descriptor_set_storage_buffers = combined_blocks;
+        // GLES 3.1 allows implementations without any storage blocks in the vertex stage.
+        if vertex_blocks != 0 {
+            features |= Features::VERTEX_STORES_AND_ATOMICS;
+        }
+        if fragment_blocks != 0 {
+            features |= Features::FRAGMENT_STORES_AND_ATOMICS;
+        }
+    }
     // TODO: Fill out downlevel features correctly.
     let mut downlevel = hal::DownlevelProperties::all_enabled();
     // TODO: Merge downlevel/legacy features?
@@ -668,6 +791,8 @@ pub(crate) fn query_all(
         Ext("GL_EXT_texture_filter_anisotropic"),
     ]) {
         features |= Features::SAMPLER_ANISOTROPY;
+        limits.max_sampler_anisotropy =
+            get_f32(gl, glow::MAX_TEXTURE_MAX_ANISOTROPY).unwrap_or(1.0);
     }
     if info.is_supported(&[Core(4, 2), Es(3, 1)]) {
         legacy |= LegacyFeatures::EXPLICIT_LAYOUTS_IN_SHADER;
@@ -682,18 +807,83 @@ pub(crate) fn query_all(
     if info.is_supported(&[Core(2, 1)]) {
         features |= Features::SAMPLER_BORDER_COLOR;
     }
+    if info.is_supported(&[
+        Core(4, 0),
+        Es(3, 2),
+        Ext("GL_ARB_sample_shading"),
+        Ext("GL_OES_sample_shading"),
+    ]) {
+        features |= Features::SAMPLE_RATE_SHADING;
+    }
     if info.is_supported(&[Core(4, 4), Ext("ARB_texture_mirror_clamp_to_edge")]) {
         features |= Features::SAMPLER_MIRROR_CLAMP_EDGE;
     }
-    if info.is_supported(&[Core(4, 0), Es(3, 2), Ext("GL_EXT_draw_buffers2")]) && !crate::is_webgl()
+    // `GL_EXT_draw_buffers2` only covers enabling blending per target, not the equations.
+    if info.is_supported(&[
+        Core(4, 0),
+        Es(3, 2),
+        Ext("GL_ARB_draw_buffers_blend"),
+        Ext("GL_OES_draw_buffers_indexed"),
+        Ext("GL_EXT_draw_buffers_indexed"),
+    ]) && !crate::is_webgl()
     {
         features |= Features::INDEPENDENT_BLENDING;
     }
 
-    // TODO
-    if false && info.is_supported(&[Core(4, 3), Es(3, 1)]) {
-        // TODO: extension
+    if info.is_supported(&[Ext("GL_EXT_texture_compression_s3tc")])
+        && info.is_supported(&[
+            Core(3, 0),
+            Ext("GL_ARB_texture_compression_rgtc"),
+            Ext("GL_EXT_texture_compression_rgtc"),
+        ])
+        && info.is_supported(&[
+            Core(4, 2),
+            Ext("GL_ARB_texture_compression_bptc"),
+            Ext("GL_EXT_texture_compression_bptc"),
+        ])
+    {
+        features |= Features::FORMAT_BC;
+    }
+    // Compressed uploads aren't exposed by `glow` on WebGL, see `ext`.
+    if info.is_supported(&[Core(4, 3), Es(3, 0), Ext("GL_ARB_ES3_compatibility")])
+        && !crate::is_webgl()
+    {
+        features |= Features::FORMAT_ETC2;
+    }
+    if info.is_supported(&[Es(3, 2), Ext("GL_KHR_texture_compression_astc_ldr")]) {
+        features |= Features::FORMAT_ASTC_LDR;
+    }
+    if info.is_supported(&[
+        Core(3, 3),
+        Ext("GL_ARB_blend_func_extended"),
+        Ext("GL_EXT_blend_func_extended"),
+    ]) {
+        features |= Features::DUAL_SRC_BLENDING;
+    }
+    if info.is_supported(&[
+        Core(3, 2),
+        Ext("GL_ARB_depth_clamp"),
+        Ext("GL_EXT_depth_clamp"),
+    ]) {
+        features |= Features::DEPTH_CLAMP;
+    }
+    let mut multi_draw_indirect = false;
+    let mut indirect_parameters = false;
+    if info.is_supported(&[Core(4, 0), Es(3, 1), Ext("GL_ARB_draw_indirect")]) && !crate::is_webgl()
+    {
         legacy |= LegacyFeatures::INDIRECT_EXECUTION;
+        // Without `glMultiDraw*Indirect`, multiple draws are issued
+        // as a sequence of single indirect draws.
+        features |= Features::MULTI_DRAW_INDIRECT;
+        multi_draw_indirect = info.is_supported(&[
+            Core(4, 3),
+            Ext("GL_ARB_multi_draw_indirect"),
+            Ext("GL_EXT_multi_draw_indirect"),
+        ]);
+        indirect_parameters = info.is_supported(&[Core(4, 6), Ext("GL_ARB_indirect_parameters")]);
+        if indirect_parameters {
+            features |= Features::DRAW_INDIRECT_COUNT;
+        }
     }
     if info.is_supported(&[Core(3, 1), Es(3, 0), Ext("GL_ARB_draw_instanced")]) {
         legacy |= LegacyFeatures::DRAW_INSTANCED;
@@ -701,23 +891,22 @@ pub(crate) fn query_all(
     if info.is_supported(&[Core(4, 2), Ext("GL_ARB_base_instance")]) {
         legacy |= LegacyFeatures::DRAW_INSTANCED_BASE;
     }
-    if info.is_supported(&[Core(3, 2)]) {
-        // TODO: extension
+    let draw_elements_base_vertex = info.is_supported(&[
+        Core(3, 2),
+        Es(3, 2),
+        Ext("GL_ARB_draw_elements_base_vertex"),
+        Ext("GL_OES_draw_elements_base_vertex"),
+        Ext("GL_EXT_draw_elements_base_vertex"),
+    ]);
+    if draw_elements_base_vertex {
         legacy |= LegacyFeatures::DRAW_INDEXED_BASE;
     }
     if info.is_supported(&[Core(3, 1), Es(3, 0)]) {
         // TODO: extension
         legacy |= LegacyFeatures::DRAW_INDEXED_INSTANCED;
     }
-    if info.is_supported(&[Core(3, 2)]) {
-        // TODO: extension
+    if draw_elements_base_vertex {
         legacy |= LegacyFeatures::DRAW_INDEXED_INSTANCED_BASE_VERTEX;
-    }
-    if info.is_supported(&[
-        Core(3, 2),
-        Es(3, 2),
-        Ext("GL_ARB_draw_elements_base_vertex"),
-    ]) {
         legacy |= LegacyFeatures::VERTEX_BASE;
     }
     if info.is_supported(&[
@@ -749,16 +938,25 @@ pub(crate) fn query_all(
     if info.is_supported(&[Core(3, 3), Es(3, 0)]) {
         legacy |= LegacyFeatures::INSTANCED_ATTRIBUTE_BINDING;
     }
+    if info.is_supported(&[Core(4, 2), Es(3, 1), Ext("GL_ARB_shader_image_load_store")]) {
+        legacy |= LegacyFeatures::IMAGE_LOAD_STORE;
+        limits
+            .descriptor_limits
+            .max_per_stage_descriptor_storage_images =
+            get_usize(gl, glow::MAX_FRAGMENT_IMAGE_UNIFORMS).unwrap_or(0) as u32;
+        limits.descriptor_limits.max_descriptor_set_storage_images =
+            get_usize(gl, glow::MAX_COMBINED_IMAGE_UNIFORMS).unwrap_or(0) as u32;
+    }
 
     let mut performance_caveats = PerformanceCaveats::empty();
-    //TODO: extension
-    if !info.is_supported(&[Core(4, 2)]) {
+    if !legacy.contains(LegacyFeatures::DRAW_INSTANCED_BASE) {
         performance_caveats |= PerformanceCaveats::BASE_VERTEX_INSTANCE_DRAWING;
     }
     let properties = PhysicalDeviceProperties {
         limits,
         performance_caveats,
-        dynamic_pipeline_states: DynamicStates::all(),
+        // Depth bounds testing isn't implemented.
+        dynamic_pipeline_states: DynamicStates::all() - DynamicStates::DEPTH_BOUNDS,
         ..PhysicalDeviceProperties::default()
     };
 
@@ -776,6 +974,7 @@ pub(crate) fn query_all(
         framebuffer: info.is_supported(&[Core(3, 0), Es(2, 0), Ext("GL_ARB_framebuffer_object")]),
         // TODO && gl.GenFramebuffers.is_loaded(),
         framebuffer_texture: info.is_supported(&[Core(3, 0)]), //TODO: double check
+        layered_rendering: info.is_supported(&[Core(3, 2), Es(3, 2)]),
         // `WebGL` Note: buffers bound to non ELEMENT_ARRAY_BUFFER targets can not be bound to ELEMENT_ARRAY_BUFFER target
         index_buffer_role_change: info.is_supported(&[Core(2, 0), Es(2, 0)]) && !crate::is_webgl(),
         image_storage: info.is_supported(&[Core(4, 2), Es(3, 0), Ext("GL_ARB_texture_storage")]),
@@ -790,6 +989,50 @@ pub(crate) fn query_all(
         per_slot_color_mask: info.is_supported(&[Core(3, 0)]),
         get_tex_image: !info.version.is_embedded,
         memory_barrier: info.is_supported(&[Core(4, 2), Es(3, 1)]),
+        gl_spirv: info.is_supported(&[Core(4, 6), Ext("GL_ARB_gl_spirv")]),
+        texture_buffer_range: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 2),
+            Ext("GL_ARB_texture_buffer_range"),
+        ]),
+        texture_view: !crate::is_webgl()
+            && info.is_supported(&[
+                Core(4, 3),
+                Ext("GL_ARB_texture_view"),
+                Ext("GL_OES_texture_view"),
+                Ext("GL_EXT_texture_view"),
+            ]),
+        primitive_restart_fixed_index: info
+            .is_supported(&[Core(4, 3), Ext("GL_ARB_ES3_compatibility")])
+            && !info.version.is_embedded,
+        program_binary: info.is_supported(&[
+            Core(4, 1),
+            Es(3, 0),
+            Ext("GL_ARB_get_program_binary"),
+        ]),
+        invalidate_framebuffer: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 0),
+            Ext("GL_ARB_invalidate_subdata"),
+        ]),
+        direct_state_access: info.is_supported(&[Core(4, 5), Ext("GL_ARB_direct_state_access")]),
+        multi_draw_indirect,
+        indirect_parameters,
+        // The reset status is only reported for contexts created with the
+        // lose-on-reset strategy, which WebGL doesn't expose.
+        robustness: !crate::is_webgl()
+            && info.is_supported(&[
+                Core(4, 5),
+                Es(3, 2),
+                Ext("GL_ARB_robustness"),
+                Ext("GL_KHR_robustness"),
+                Ext("GL_EXT_robustness"),
+            ])
+            && get_usize(gl, glow::RESET_NOTIFICATION_STRATEGY).ok()
+                == Some(glow::LOSE_CONTEXT_ON_RESET as usize),
+        internal_format_query: !crate::is_webgl()
+            && info.is_supported(&[Core(4, 2), Es(3, 0), Ext("GL_ARB_internalformat_query")]),
+        integer_sample_counts,
     };
 
     let filter = if info.is_supported(&[Es(3, 0)]) {
@@ -40,6 +40,7 @@ pub use self::info::{Info, PlatformName, Version};
 mod command;
 mod conv;
 mod device;
+mod ext;
 mod info;
 mod native;
 mod pool;
@@ -69,6 +70,7 @@ const MAX_COLOR_ATTACHMENTS: usize = 16;
 
 struct GlContainer {
     context: GlContext,
+    ext: ext::ExtFns,
 }
 
 impl Deref for GlContainer {
@@ -108,7 +110,7 @@ impl hal::Backend for Backend {
     type ComputePipeline = native::ComputePipeline;
     type GraphicsPipeline = native::GraphicsPipeline;
     type PipelineLayout = native::PipelineLayout;
-    type PipelineCache = ();
+    type PipelineCache = native::PipelineCache;
     type DescriptorSetLayout = native::DescriptorSetLayout;
     type DescriptorPool = native::DescriptorPool;
     type DescriptorSet = native::DescriptorSet;
@@ -130,6 +132,7 @@ pub enum Error {
     InvalidOperation,
     InvalidFramebufferOperation,
     OutOfMemory,
+    ContextLost,
     UnknownError,
 }
 
@@ -142,30 +145,35 @@ impl Error {
             glow::INVALID_OPERATION => Error::InvalidOperation,
             glow::INVALID_FRAMEBUFFER_OPERATION => Error::InvalidFramebufferOperation,
             glow::OUT_OF_MEMORY => Error::OutOfMemory,
+            glow::CONTEXT_LOST => Error::ContextLost,
             _ => Error::UnknownError,
         }
     }
 }
 
-fn debug_message_callback(source: u32, gltype: u32, id: u32, severity: u32, message: &str) {
-    let source_str = match source {
-        glow::DEBUG_SOURCE_API => "API",
-        glow::DEBUG_SOURCE_WINDOW_SYSTEM => "Window System",
-        glow::DEBUG_SOURCE_SHADER_COMPILER => "ShaderCompiler",
-        glow::DEBUG_SOURCE_THIRD_PARTY => "Third Party",
-        glow::DEBUG_SOURCE_APPLICATION => "Application",
-        glow::DEBUG_SOURCE_OTHER => "Other",
-        _ => unreachable!(),
-    };
-
-    let log_severity = match severity {
+fn map_debug_severity(severity: u32) -> log::Level {
+    match severity {
         glow::DEBUG_SEVERITY_HIGH => log::Level::Error,
         glow::DEBUG_SEVERITY_MEDIUM => log::Level::Warn,
         glow::DEBUG_SEVERITY_LOW => log::Level::Info,
         glow::DEBUG_SEVERITY_NOTIFICATION => log::Level::Trace,
-        _ => unreachable!(),
+        _ => log::Level::Warn,
+    }
+}
+
+fn debug_message_callback(source: u32, gltype: u32, id: u32, severity: u32, message: &str) {
+    // Each source logs to its own target, so they can be filtered separately.
+    let target = match source {
+        glow::DEBUG_SOURCE_API => "gfx_backend_gl::debug::api",
+        glow::DEBUG_SOURCE_WINDOW_SYSTEM => "gfx_backend_gl::debug::window_system",
+        glow::DEBUG_SOURCE_SHADER_COMPILER => "gfx_backend_gl::debug::shader_compiler",
+        glow::DEBUG_SOURCE_THIRD_PARTY => "gfx_backend_gl::debug::third_party",
+        glow::DEBUG_SOURCE_APPLICATION => "gfx_backend_gl::debug::application",
+        _ => "gfx_backend_gl::debug::other",
     };
 
+    let log_severity = map_debug_severity(severity);
+
     let type_str = match gltype {
         glow::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated Behavior",
         glow::DEBUG_TYPE_ERROR => "Error",
@@ -176,13 +184,13 @@ fn debug_message_callback(source: u32, gltype: u32, id: u32, severity: u32, mess
         glow::DEBUG_TYPE_PORTABILITY => "Portability",
         glow::DEBUG_TYPE_PUSH_GROUP => "Push Group",
         glow::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined Behavior",
-        _ => unreachable!(),
+        _ => "Unknown",
     };
 
     log::log!(
+        target: target,
         log_severity,
-        "[{}/{}] ID {} : {}",
-        source_str,
+        "[{}] ID {} : {}",
         type_str,
         id,
         message
@@ -227,6 +235,18 @@ impl Share {
         Ok(())
     }
 
+    /// Reports the device as lost if the context was reset.
+    fn check_context_lost(&self) -> Result<(), hal::device::DeviceLost> {
+        if self.private_caps.robustness {
+            let status = unsafe { self.context.get_graphics_reset_status() };
+            if status != glow::NO_ERROR {
+                log::error!("Context was lost with status 0x{:x}", status);
+                return Err(hal::device::DeviceLost);
+            }
+        }
+        Ok(())
+    }
+
     fn buffer_memory_type_mask(&self, usage: buffer::Usage) -> u32 {
         let mut type_mask = 0;
         for (type_index, &(_, kind)) in self.memory_types.iter().enumerate() {
@@ -354,8 +374,8 @@ unsafe impl<T: ?Sized> Sync for Wstarc<T> {}
 pub struct PhysicalDevice(Starc<Share>);
 
 impl PhysicalDevice {
-    fn new_adapter(context: GlContext) -> adapter::Adapter<Backend> {
-        let gl = GlContainer { context };
+    fn new_adapter(context: GlContext, ext: ext::ExtFns) -> adapter::Adapter<Backend> {
+        let gl = GlContainer { context, ext };
         // query information
         let (
             info,
@@ -555,10 +575,16 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         // initialize permanent states
         let gl = &self.0.context;
 
-        if cfg!(debug_assertions) && !cfg!(target_arch = "wasm32") && gl.supports_debug() {
+        // Release builds can opt into the driver debug output with `GFX_GL_DEBUG`.
+        let debug_output = cfg!(debug_assertions) || std::env::var("GFX_GL_DEBUG").is_ok();
+        if debug_output && !cfg!(target_arch = "wasm32") && gl.supports_debug() {
             log::info!("Debug output is enabled");
             gl.enable(glow::DEBUG_OUTPUT);
             gl.debug_message_callback(debug_message_callback);
+            // Low severity messages are disabled by default. Everything is forwarded,
+            // and filtered by the logger with the per-source targets, since the log
+            // level may change after the device is opened.
+            gl.debug_message_control(glow::DONT_CARE, glow::DONT_CARE, glow::DONT_CARE, &[], true);
         }
 
         if self
@@ -573,6 +599,7 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         }
 
         gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
 
         // create main VAO and bind it
         let mut vao = None;
@@ -600,34 +627,107 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         })
     }
 
-    fn format_properties(&self, _: Option<hal::format::Format>) -> hal::format::Properties {
-        use hal::format::{BufferFeature as Bf, ImageFeature as If};
+    fn format_properties(&self, format: Option<hal::format::Format>) -> hal::format::Properties {
+        use hal::format::{BufferFeature as Bf, ChannelType, ImageFeature as If};
 
-        // TODO: These are for show
-        hal::format::Properties {
-            linear_tiling: If::TRANSFER_SRC | If::TRANSFER_DST | If::empty(),
-            optimal_tiling: If::TRANSFER_SRC | If::TRANSFER_DST | If::SAMPLED,
+        let format = match format {
+            Some(format) => format,
+            None => return hal::format::Properties::default(),
+        };
+        let mut props = hal::format::Properties {
             buffer_features: Bf::VERTEX,
-            drm_format_properties: Vec::new(),
+            ..hal::format::Properties::default()
+        };
+
+        let desc = match conv::describe_format(format, self.0.info.version.is_embedded) {
+            Some(desc) => desc,
+            None => return props,
+        };
+        let is_compressed = format.surface_desc().is_compressed();
+        let supported = match conv::compressed_format_feature(format) {
+            Some(feature) => self.0.supported_features.contains(feature),
+            None => self.0.texture_format_filter.check(
+                desc.tex_internal,
+                desc.tex_external,
+                desc.data_type,
+            ),
+        };
+        if !supported {
+            return props;
+        }
+
+        let is_integer = match format.base_format().1 {
+            ChannelType::Uint | ChannelType::Sint => true,
+            _ => false,
+        };
+        let mut features = If::SAMPLED | If::TRANSFER_SRC | If::TRANSFER_DST | If::BLIT_SRC;
+        if !is_integer {
+            features |= If::SAMPLED_LINEAR;
         }
+        let is_embedded = self.0.info.version.is_embedded;
+        let float_renderable = self.0.info.is_supported(&[
+            info::Requirement::Es(3, 2),
+            info::Requirement::Ext("GL_EXT_color_buffer_float"),
+        ]);
+        if format.is_depth() || format.is_stencil() {
+            features |= If::DEPTH_STENCIL_ATTACHMENT;
+        } else if !is_compressed
+            && conv::is_color_renderable(desc.tex_internal, is_embedded, float_renderable)
+        {
+            features |= If::COLOR_ATTACHMENT | If::BLIT_DST;
+            if !is_integer {
+                features |= If::COLOR_ATTACHMENT_BLEND;
+            }
+        }
+        // BGRA is only an external layout on desktop GL, the shaders see RGBA.
+        if !is_compressed
+            && desc.tex_external != glow::BGRA
+            && conv::is_storage_format(desc.tex_internal, is_embedded)
+            && self
+                .0
+                .legacy_features
+                .contains(info::LegacyFeatures::IMAGE_LOAD_STORE)
+        {
+            features |= If::STORAGE;
+        }
+        props.optimal_tiling = features;
+        props.linear_tiling = If::TRANSFER_SRC | If::TRANSFER_DST;
+
+        if self.0.public_caps.limits.max_texel_elements != 0 && !is_compressed {
+            props.buffer_features |= Bf::UNIFORM_TEXEL;
+        }
+        props
     }
 
     fn image_format_properties(
         &self,
         format: hal::format::Format,
-        _dimensions: u8,
+        dimensions: u8,
         _tiling: image::Tiling,
-        _usage: image::Usage,
+        usage: image::Usage,
         _view_caps: image::ViewCapabilities,
     ) -> Option<image::FormatProperties> {
+        // Shared exponent and compressed formats aren't color renderable.
+        if usage.contains(image::Usage::COLOR_ATTACHMENT)
+            && (format == hal::format::Format::E5b9g9r9Ufloat
+                || format.surface_desc().is_compressed())
+        {
+            return None;
+        }
+
         let conv::FormatDescription {
             tex_external,
             tex_internal,
             data_type,
             ..
-        } = conv::describe_format(format)?;
+        } = conv::describe_format(format, self.0.info.version.is_embedded)?;
 
-        if !self
+        if let Some(feature) = conv::compressed_format_feature(format) {
+            // Compressed formats aren't part of the `glTexImage` tables.
+            if !self.0.supported_features.contains(feature) {
+                return None;
+            }
+        } else if !self
             .0
             .texture_format_filter
             .check(tex_internal, tex_external, data_type)
@@ -636,6 +736,38 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             return None;
         }
 
+        // Only 2D attachments are backed by multisampled renderbuffers,
+        // sampled and storage images are always single-sampled textures.
+        let limits = &self.0.public_caps.limits;
+        let mut sample_count_mask =
+            if dimensions != 2 || usage.intersects(image::Usage::SAMPLED | image::Usage::STORAGE) {
+                0x1
+            } else if usage.contains(image::Usage::DEPTH_STENCIL_ATTACHMENT) {
+                limits.framebuffer_depth_sample_counts & limits.framebuffer_stencil_sample_counts
+            } else if usage.contains(image::Usage::COLOR_ATTACHMENT) {
+                limits.framebuffer_color_sample_counts
+            } else {
+                0x1
+            };
+        if sample_count_mask != 0x1 {
+            let private_caps = &self.0.private_caps;
+            match format.base_format().1 {
+                hal::format::ChannelType::Uint | hal::format::ChannelType::Sint => {
+                    sample_count_mask &= private_caps.integer_sample_counts;
+                }
+                _ => {}
+            }
+            // Drivers may support fewer samples than `GL_MAX_SAMPLES` for some formats.
+            if private_caps.internal_format_query {
+                let samples = unsafe {
+                    self.0
+                        .context
+                        .get_internal_format_samples(glow::RENDERBUFFER, tex_internal)
+                };
+                sample_count_mask &= samples.iter().fold(0x1, |mask, &count| mask | count as u8);
+            }
+        }
+
         Some(image::FormatProperties {
             max_extent: image::Extent {
                 width: !0,
@@ -644,7 +776,7 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             },
             max_levels: !0,
             max_layers: !0,
-            sample_count_mask: 127,
+            sample_count_mask,
             max_resource_size: !0,
         })
     }
@@ -1,12 +1,13 @@
-use crate::{Backend, GlContext, MAX_TEXTURE_SLOTS};
+use crate::{Backend, FastHashMap, GlContext, MAX_TEXTURE_SLOTS};
 
 use hal::{
     buffer, format, image as i,
     memory::{Properties, Requirements},
     pass, pso, window as w,
 };
+use parking_lot::Mutex;
 
-use std::{borrow::Borrow, fmt, ops::Range, sync::Arc};
+use std::{borrow::Borrow, convert::TryInto, fmt, ops::Range, sync::Arc};
 
 pub type TextureTarget = u32;
 pub type TextureFormat = u32;
@@ -36,6 +37,8 @@ pub enum Buffer {
     Unbound {
         size: buffer::Offset,
         usage: buffer::Usage,
+        /// Debug name, applied to the GL buffer once memory is bound.
+        name: Option<String>,
     },
     Bound {
         buffer: RawBuffer,
@@ -69,7 +72,11 @@ impl Buffer {
 }
 
 #[derive(Debug)]
-pub struct BufferView;
+pub struct BufferView {
+    /// Buffer texture object sourcing its texels from the buffer range.
+    pub(crate) raw: Texture,
+    pub(crate) format: TextureFormat,
+}
 
 #[derive(Debug)]
 pub enum Fence {
@@ -83,6 +90,7 @@ unsafe impl Sync for Fence {}
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum BindingRegister {
     Textures,
+    Images,
     UniformBuffers,
     StorageBuffers,
 }
@@ -96,19 +104,145 @@ pub struct GraphicsPipeline {
     pub(crate) program: Program,
     pub(crate) primitive: u32,
     pub(crate) patch_size: Option<i32>,
+    pub(crate) primitive_restart: bool,
     pub(crate) blend_targets: Vec<pso::ColorBlendDesc>,
     pub(crate) attributes: Vec<AttributeDesc>,
     pub(crate) vertex_buffers: Vec<Option<pso::VertexBufferDesc>>,
     pub(crate) uniforms: Vec<UniformDesc>,
     pub(crate) rasterizer: pso::Rasterizer,
+    pub(crate) multisampling: Option<pso::Multisampling>,
     pub(crate) depth: Option<pso::DepthTest>,
     pub(crate) baked_states: pso::BakedStates,
     pub(crate) sampler_map: SamplerBindMap,
 }
 
+/// Linked program binary along with the binding state that is lost
+/// when the program is reloaded from it.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedProgram {
+    pub(crate) format: u32,
+    pub(crate) binary: Vec<u8>,
+    pub(crate) sampler_map: SamplerBindMap,
+    pub(crate) name_binding_map: FastHashMap<String, (BindingRegister, u8)>,
+}
+
+/// Program binaries (`GL_ARB_get_program_binary`), keyed by a hash of the
+/// shader stages and the pipeline layout they were compiled against.
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    pub(crate) programs: Mutex<FastHashMap<u64, CachedProgram>>,
+}
+
+const PIPELINE_CACHE_MAGIC: &[u8; 8] = b"GFXGLPC1";
+
+impl PipelineCache {
+    /// Restore a cache from `get_pipeline_cache_data` output.
+    /// Malformed or foreign data yields an empty cache.
+    pub(crate) fn from_data(data: &[u8]) -> Self {
+        let programs = Self::parse(data).unwrap_or_else(|| {
+            log::warn!("Ignoring invalid pipeline cache data");
+            FastHashMap::default()
+        });
+        PipelineCache {
+            programs: Mutex::new(programs),
+        }
+    }
+
+    fn parse(data: &[u8]) -> Option<FastHashMap<u64, CachedProgram>> {
+        struct Reader<'a>(&'a [u8]);
+        impl<'a> Reader<'a> {
+            fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+                if self.0.len() < len {
+                    return None;
+                }
+                let (head, tail) = self.0.split_at(len);
+                self.0 = tail;
+                Some(head)
+            }
+            fn u8(&mut self) -> Option<u8> {
+                self.bytes(1).map(|b| b[0])
+            }
+            fn u32(&mut self) -> Option<u32> {
+                self.bytes(4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            }
+            fn u64(&mut self) -> Option<u64> {
+                self.bytes(8)
+                    .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            }
+        }
+
+        let mut reader = Reader(data);
+        if reader.bytes(PIPELINE_CACHE_MAGIC.len())? != PIPELINE_CACHE_MAGIC {
+            return None;
+        }
+        let mut programs = FastHashMap::default();
+        for _ in 0..reader.u32()? {
+            let key = reader.u64()?;
+            let format = reader.u32()?;
+            let len = reader.u32()? as usize;
+            let binary = reader.bytes(len)?.to_vec();
+            let mut sampler_map = [None; MAX_TEXTURE_SLOTS];
+            for slot in sampler_map.iter_mut() {
+                *slot = match reader.u8()? {
+                    0xFF => None,
+                    sampler => Some(sampler),
+                };
+            }
+            let mut name_binding_map = FastHashMap::default();
+            for _ in 0..reader.u32()? {
+                let len = reader.u32()? as usize;
+                let name = String::from_utf8(reader.bytes(len)?.to_vec()).ok()?;
+                let register = match reader.u8()? {
+                    0 => BindingRegister::Textures,
+                    1 => BindingRegister::Images,
+                    2 => BindingRegister::UniformBuffers,
+                    3 => BindingRegister::StorageBuffers,
+                    _ => return None,
+                };
+                let slot = reader.u8()?;
+                name_binding_map.insert(name, (register, slot));
+            }
+            programs.insert(
+                key,
+                CachedProgram {
+                    format,
+                    binary,
+                    sampler_map,
+                    name_binding_map,
+                },
+            );
+        }
+        Some(programs)
+    }
+
+    /// Serialize the cache for `create_pipeline_cache`.
+    pub(crate) fn to_data(&self) -> Vec<u8> {
+        let programs = self.programs.lock();
+        let mut data = PIPELINE_CACHE_MAGIC.to_vec();
+        data.extend_from_slice(&(programs.len() as u32).to_le_bytes());
+        for (key, program) in programs.iter() {
+            data.extend_from_slice(&key.to_le_bytes());
+            data.extend_from_slice(&program.format.to_le_bytes());
+            data.extend_from_slice(&(program.binary.len() as u32).to_le_bytes());
+            data.extend_from_slice(&program.binary);
+            data.extend(program.sampler_map.iter().map(|s| s.unwrap_or(0xFF)));
+            data.extend_from_slice(&(program.name_binding_map.len() as u32).to_le_bytes());
+            for (name, &(register, slot)) in program.name_binding_map.iter() {
+                data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+                data.extend_from_slice(name.as_bytes());
+                data.push(register as u8);
+                data.push(slot);
+            }
+        }
+        data
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ComputePipeline {
     pub(crate) program: Program,
+    pub(crate) uniforms: Vec<UniformDesc>,
     pub(crate) sampler_map: SamplerBindMap,
 }
 
@@ -127,9 +261,12 @@ pub struct Image {
 impl Image {
     pub(crate) fn pitches(&self, level: i::Level) -> [buffer::Offset; 4] {
         let extent = self.kind.extent().at_level(level);
+        let (block_width, block_height) = self.format_desc.dim;
         let bytes_per_texel = self.format_desc.bits as i::Size >> 3;
-        let row_pitch = extent.width * bytes_per_texel;
-        let depth_pitch = extent.height * row_pitch;
+        let row_pitch =
+            (extent.width + block_width as i::Size - 1) / block_width as i::Size * bytes_per_texel;
+        let depth_pitch =
+            (extent.height + block_height as i::Size - 1) / block_height as i::Size * row_pitch;
         let array_pitch = extent.depth * depth_pitch;
         [
             bytes_per_texel as _,
@@ -145,6 +282,7 @@ pub enum ImageType {
     Renderbuffer {
         raw: Renderbuffer,
         format: TextureFormat,
+        pixel_type: DataType,
     },
     Texture {
         target: TextureTarget,
@@ -152,6 +290,7 @@ pub enum ImageType {
         level_count: i::Level,
         layer_count: i::Layer,
         format: TextureFormat,
+        internal_format: TextureFormat,
         pixel_type: DataType,
     },
 }
@@ -173,8 +312,13 @@ pub enum ImageView {
     Texture {
         target: TextureTarget,
         raw: Texture,
+        /// Internal format, used when binding the view as a storage image.
+        format: TextureFormat,
         is_3d: bool,
         sub: i::SubresourceRange,
+        /// Texture view aliasing `sub`, sampled instead of `raw` when the view
+        /// doesn't cover the whole texture.
+        view: Option<(TextureTarget, Texture)>,
     },
 }
 
@@ -210,6 +354,7 @@ impl SwapchainImage {
     pub(crate) fn new(
         renderbuffer: Renderbuffer,
         format: TextureFormat,
+        pixel_type: DataType,
         extent: w::Extent2D,
         channel: format::ChannelType,
     ) -> Self {
@@ -218,6 +363,7 @@ impl SwapchainImage {
                 object_type: ImageType::Renderbuffer {
                     raw: renderbuffer,
                     format,
+                    pixel_type,
                 },
                 channel,
                 kind: i::Kind::D2(extent.width as u32, extent.height as u32, 1, 1),
@@ -251,16 +397,32 @@ pub(crate) enum DescSetBindings {
         offset: i32,
         size: i32,
     },
-    Texture(Texture, TextureTarget),
+    Texture(Texture, TextureTarget, Range<i::Level>),
+    Image {
+        texture: Texture,
+        level: i::Level,
+        /// Single bound layer, or `None` to bind all the layers.
+        layer: Option<i::Layer>,
+        access: u32,
+        format: TextureFormat,
+    },
     Sampler(Sampler),
     SamplerDesc(i::SamplerDesc),
 }
 
+/// A descriptor written into a set, along with the array element it fills.
+#[derive(Clone, Debug)]
+pub struct DescSetEntry {
+    pub(crate) binding: pso::DescriptorBinding,
+    pub(crate) array_index: pso::DescriptorArrayIndex,
+    pub(crate) value: DescSetBindings,
+}
+
 #[derive(Clone, Debug)]
 pub struct DescriptorSet {
     pub(crate) layout: DescriptorSetLayout,
     //TODO: use `UnsafeCell` instead
-    pub(crate) bindings: Vec<DescSetBindings>,
+    pub(crate) bindings: Vec<DescSetEntry>,
 }
 
 #[derive(Debug)]
@@ -292,7 +454,7 @@ impl pso::DescriptorPool<Backend> for DescriptorPool {
 }
 
 pub struct ShaderModule {
-    #[cfg(feature = "cross")]
+    /// SPIR-V words, used by SPIRV-Cross and by `GL_ARB_gl_spirv`.
     pub(crate) spv: Vec<u32>,
     pub(crate) naga: Result<hal::device::NagaShader, String>,
 }
@@ -328,9 +490,17 @@ pub struct RenderPass {
 pub struct SubpassDesc {
     pub(crate) color_attachments: Vec<usize>,
     pub(crate) depth_stencil: Option<usize>,
+    pub(crate) input_attachments: Vec<usize>,
 }
 
 impl SubpassDesc {
+    /// Check if an attachment is written or read by this sub-pass.
+    pub(crate) fn uses_attachment(&self, at_id: pass::AttachmentId) -> bool {
+        self.depth_stencil == Some(at_id)
+            || self.color_attachments.contains(&at_id)
+            || self.input_attachments.contains(&at_id)
+    }
+
     /// Check if an attachment is used by this sub-pass.
     pub(crate) fn _attachment_using(&self, at_id: pass::AttachmentId) -> Option<u32> {
         if self.depth_stencil == Some(at_id) {
@@ -58,6 +58,7 @@ pub struct CommandPool {
     pub(crate) fbo: Option<n::RawFramebuffer>,
     pub(crate) limits: command::Limits,
     pub(crate) memory: Arc<Mutex<BufferMemory>>,
+    pub(crate) features: hal::Features,
     pub(crate) legacy_features: info::LegacyFeatures,
 }
 
@@ -88,6 +89,7 @@ impl hal::pool::CommandPool<Backend> for CommandPool {
             self.fbo,
             self.limits,
             self.memory.clone(),
+            self.features,
             self.legacy_features,
         )
     }
@@ -1,12 +1,12 @@
 use crate::{
     command as com, device, info::LegacyFeatures, native, state, Backend, Device, GlContext, Share,
-    Starc, Surface, MAX_COLOR_ATTACHMENTS,
+    Starc, Surface, MAX_COLOR_ATTACHMENTS, MAX_SAMPLERS, MAX_TEXTURE_SLOTS,
 };
 
 use arrayvec::ArrayVec;
 use glow::HasContext;
 
-use std::{mem, slice};
+use std::{mem, ops::Range, slice, str};
 
 // State caching system for command queue.
 //
@@ -27,6 +27,17 @@ struct State {
     num_viewports: usize,
     // Currently set scissor rects.
     num_scissors: usize,
+    // Currently bound program.
+    program: Option<native::Program>,
+    // Currently bound texture of each texture unit.
+    // The active texture unit isn't cached, it's always set before binding.
+    textures: [Option<(native::TextureTarget, native::Texture)>; MAX_TEXTURE_SLOTS],
+    // Currently bound sampler object of each texture unit.
+    samplers: [Option<native::Sampler>; MAX_SAMPLERS],
+    // Currently set blend state, `None` if unknown.
+    blend: Option<Option<hal::pso::BlendState>>,
+    // Currently set depth test, `None` if unknown.
+    depth_func: Option<Option<hal::pso::Comparison>>,
 }
 
 impl State {
@@ -38,6 +49,29 @@ impl State {
             index_buffer: None,
             num_viewports: 0,
             num_scissors: 0,
+            program: None,
+            textures: [None; MAX_TEXTURE_SLOTS],
+            samplers: [None; MAX_SAMPLERS],
+            blend: None,
+            depth_func: None,
+        }
+    }
+
+    // Forget the cached pipeline state and resource bindings.
+    // Required whenever other code may have touched the context.
+    fn reset_bindings(&mut self) {
+        self.program = None;
+        self.textures = [None; MAX_TEXTURE_SLOTS];
+        self.samplers = [None; MAX_SAMPLERS];
+        self.blend = None;
+        self.depth_func = None;
+    }
+
+    // Forget the cached texture binding of a texture unit.
+    // Required whenever a unit is rebound outside of `BindTexture`.
+    fn invalidate_texture(&mut self, unit: u32) {
+        if let Some(cached) = self.textures.get_mut(unit as usize) {
+            *cached = None;
         }
     }
 
@@ -46,6 +80,7 @@ impl State {
     fn flush(&mut self) {
         self.vao = false;
         self.index_buffer = None;
+        self.reset_bindings();
 
         // TOOD: reset viewports and scissors
         //       do we need to clear everything from 0..MAX_VIEWPORTS?
@@ -175,10 +210,43 @@ impl Queue {
         Device::bind_target(&self.share.context, point, attachment, view)
     }
 
-    fn _unbind_target(&mut self, point: u32, attachment: u32) {
+    // Attach a single level and layer of an image to the bound framebuffer.
+    fn bind_image_level(
+        &mut self,
+        point: u32,
+        attachment: u32,
+        image: native::ImageType,
+        level: hal::image::Level,
+        layer: hal::image::Layer,
+    ) {
         let gl = &self.share.context;
-        // TODO: Find workaround or use explicit `textarget` with the other `framebuffer_texture`
-        unsafe { gl.framebuffer_texture(point, attachment, None, 0) };
+        match image {
+            native::ImageType::Renderbuffer { raw, .. } => unsafe {
+                gl.framebuffer_renderbuffer(point, attachment, glow::RENDERBUFFER, Some(raw));
+            },
+            native::ImageType::Texture {
+                target: glow::TEXTURE_2D,
+                raw,
+                ..
+            } => unsafe {
+                gl.framebuffer_texture_2d(
+                    point,
+                    attachment,
+                    glow::TEXTURE_2D,
+                    Some(raw),
+                    level as _,
+                );
+            },
+            native::ImageType::Texture { raw, .. } => unsafe {
+                gl.framebuffer_texture_layer(point, attachment, Some(raw), level as _, layer as _);
+            },
+        }
+    }
+
+    fn unbind_target(&mut self, point: u32, attachment: u32) {
+        let gl = &self.share.context;
+        // Attaching no renderbuffer detaches textures as well.
+        unsafe { gl.framebuffer_renderbuffer(point, attachment, glow::RENDERBUFFER, None) };
     }
 
     /// Return a reference to a stored data object.
@@ -222,6 +290,10 @@ impl Queue {
         unsafe { gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None) };
         self.state.index_buffer = None;
 
+        // The device shares the context and may have bound other objects
+        // since the last submission.
+        self.state.reset_bindings();
+
         // Reset viewports && scissors
         unsafe {
             gl.viewport(0, 0, 0, 0);
@@ -367,6 +439,82 @@ impl Queue {
                     log::error!("Instanced indexed drawing is not supported");
                 }
             }
+            com::Command::DrawIndirect {
+                primitive,
+                buffer,
+                offset,
+                count_buffer,
+                draw_count,
+                stride,
+            } => {
+                let gl = &self.share.context;
+                let caps = &self.share.private_caps;
+                unsafe {
+                    gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(buffer));
+                    if let Some((count_buffer, count_offset)) = count_buffer {
+                        // Only exposed with `DRAW_INDIRECT_COUNT`, which requires `indirect_parameters`.
+                        gl.bind_buffer(glow::PARAMETER_BUFFER, Some(count_buffer));
+                        gl.multi_draw_arrays_indirect_count_offset(
+                            primitive,
+                            offset as i32,
+                            count_offset as i32,
+                            draw_count as i32,
+                            stride as i32,
+                        );
+                    } else if caps.multi_draw_indirect {
+                        gl.multi_draw_arrays_indirect_offset(
+                            primitive,
+                            offset as i32,
+                            draw_count as i32,
+                            stride as i32,
+                        );
+                    } else {
+                        for draw in 0..draw_count {
+                            let offset = offset + draw as u64 * stride as u64;
+                            gl.draw_arrays_indirect_offset(primitive, offset as i32);
+                        }
+                    }
+                }
+            }
+            com::Command::DrawIndexedIndirect {
+                primitive,
+                index_type,
+                buffer,
+                offset,
+                count_buffer,
+                draw_count,
+                stride,
+            } => {
+                let gl = &self.share.context;
+                let caps = &self.share.private_caps;
+                unsafe {
+                    gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(buffer));
+                    if let Some((count_buffer, count_offset)) = count_buffer {
+                        gl.bind_buffer(glow::PARAMETER_BUFFER, Some(count_buffer));
+                        gl.multi_draw_elements_indirect_count_offset(
+                            primitive,
+                            index_type,
+                            offset as i32,
+                            count_offset as i32,
+                            draw_count as i32,
+                            stride as i32,
+                        );
+                    } else if caps.multi_draw_indirect {
+                        gl.multi_draw_elements_indirect_offset(
+                            primitive,
+                            index_type,
+                            offset as i32,
+                            draw_count as i32,
+                            stride as i32,
+                        );
+                    } else {
+                        for draw in 0..draw_count {
+                            let offset = offset + draw as u64 * stride as u64;
+                            gl.draw_elements_indirect_offset(primitive, index_type, offset as i32);
+                        }
+                    }
+                }
+            }
             com::Command::Dispatch(count) => {
                 // Capability support is given by which queue types will be exposed.
                 // If there is no compute support, this pattern should never be reached
@@ -401,7 +549,7 @@ impl Queue {
                         && num_viewports <= self.share.public_caps.limits.max_viewports
                 );
 
-                if num_viewports == 1 {
+                if num_viewports == 1 && first_viewport == 0 {
                     let view = viewports[0];
                     let depth_range = depth_ranges[0];
                     unsafe {
@@ -418,7 +566,7 @@ impl Queue {
                             gl.depth_range_f32(depth_range[0] as f32, depth_range[1] as f32);
                         }
                     };
-                } else if num_viewports > 1 {
+                } else {
                     // Support for these functions is coupled with the support
                     // of multiple viewports.
                     unsafe {
@@ -439,7 +587,7 @@ impl Queue {
                     0 < num_scissors && num_scissors <= self.share.public_caps.limits.max_viewports
                 );
 
-                if num_scissors == 1 {
+                if num_scissors == 1 && first_scissor == 0 {
                     let scissor = scissors[0];
                     unsafe { gl.scissor(scissor[0], scissor[1], scissor[2], scissor[3]) };
                 } else {
@@ -451,6 +599,14 @@ impl Queue {
             com::Command::SetBlendColor(color) => {
                 state::set_blend_color(&self.share.context, color);
             }
+            com::Command::SetLineWidth(width) => unsafe {
+                self.share.context.line_width(width);
+            },
+            com::Command::SetDepthBias(bias) => unsafe {
+                self.share
+                    .context
+                    .polygon_offset(bias.slope_factor as _, bias.const_factor as _);
+            },
             com::Command::ClearBufferColorF(draw_buffer, mut cv) => unsafe {
                 self.share
                     .context
@@ -484,6 +640,33 @@ impl Queue {
                 };
             },
             com::Command::ClearTexture(_color) => unimplemented!(),
+            com::Command::InvalidateAttachments(ref attachments) => {
+                if self.share.private_caps.invalidate_framebuffer {
+                    unsafe {
+                        self.share
+                            .context
+                            .invalidate_framebuffer(glow::DRAW_FRAMEBUFFER, attachments);
+                    }
+                }
+            }
+            com::Command::SetFramebufferSrgb(enable) => {
+                // `FRAMEBUFFER_SRGB` can't be toggled on embedded targets.
+                if self
+                    .share
+                    .legacy_features
+                    .contains(LegacyFeatures::SRGB_COLOR)
+                    && !self.share.info.version.is_embedded
+                {
+                    let gl = &self.share.context;
+                    unsafe {
+                        if enable {
+                            gl.enable(glow::FRAMEBUFFER_SRGB);
+                        } else {
+                            gl.disable(glow::FRAMEBUFFER_SRGB);
+                        }
+                    }
+                }
+            }
             com::Command::BindFramebuffer {
                 target,
                 framebuffer,
@@ -495,6 +678,12 @@ impl Queue {
                 for (i, view) in colors.iter().enumerate() {
                     self.bind_target(target, glow::COLOR_ATTACHMENT0 + i as u32, view);
                 }
+                // Detach whatever a previous subpass left attached.
+                let max_color_attachments = self.share.public_caps.limits.max_color_attachments;
+                for i in colors.len()..max_color_attachments {
+                    self.unbind_target(target, glow::COLOR_ATTACHMENT0 + i as u32);
+                }
+                self.unbind_target(target, glow::DEPTH_STENCIL_ATTACHMENT);
                 if let Some(ref view) = *depth_stencil {
                     let aspects = view.aspects();
                     let attachment = if aspects == hal::format::Aspects::DEPTH {
@@ -556,13 +745,29 @@ impl Queue {
                     .context
                     .patch_parameter_i32(glow::PATCH_VERTICES, num);
             },
-            com::Command::BindProgram(program) => unsafe {
-                self.share.context.use_program(Some(program));
-            },
+            com::Command::SetPrimitiveRestart(enable) => {
+                if self.share.private_caps.primitive_restart_fixed_index {
+                    let gl = &self.share.context;
+                    match enable {
+                        true => unsafe { gl.enable(glow::PRIMITIVE_RESTART_FIXED_INDEX) },
+                        false => unsafe { gl.disable(glow::PRIMITIVE_RESTART_FIXED_INDEX) },
+                    }
+                }
+            }
+            com::Command::BindProgram(program) => {
+                if self.state.program != Some(program) {
+                    unsafe { self.share.context.use_program(Some(program)) };
+                    self.state.program = Some(program);
+                }
+            }
             com::Command::SetBlend(ref blend) => {
-                state::set_blend(&self.share.context, blend);
+                if self.state.blend != Some(*blend) {
+                    state::set_blend(&self.share.context, blend);
+                    self.state.blend = Some(*blend);
+                }
             }
             com::Command::SetBlendSlot(slot, ref blend) => {
+                self.state.blend = None;
                 if self.share.private_caps.draw_buffers {
                     state::set_blend_slot(&self.share.context, slot, blend, &self.features);
                 } else {
@@ -625,42 +830,53 @@ impl Queue {
                 dst_target,
                 data,
             } => unsafe {
-                let is_index_buffer_only_element_dst =
-                    !self.share.private_caps.index_buffer_role_change
-                        && dst_target == glow::ELEMENT_ARRAY_BUFFER;
-
-                let copy_src_target = glow::COPY_READ_BUFFER;
-                // WebGL not allowed to copy data from other targets to element buffer and can't copy element data to other buffers
-                let copy_dst_target = if is_index_buffer_only_element_dst {
-                    glow::ELEMENT_ARRAY_BUFFER
-                } else {
-                    glow::COPY_WRITE_BUFFER
-                };
                 let gl = &self.share.context;
 
-                gl.bind_buffer(copy_src_target, Some(src_buffer));
-                gl.bind_buffer(copy_dst_target, Some(dst_buffer));
-
-                if is_index_buffer_only_element_dst {
-                    let mut buffer_data = vec![0; data.size as usize];
-                    gl.get_buffer_sub_data(copy_src_target, data.src as i32, &mut buffer_data);
-                    gl.buffer_sub_data_u8_slice(copy_dst_target, data.dst as i32, &buffer_data);
-                } else {
-                    gl.copy_buffer_sub_data(
-                        copy_src_target,
-                        copy_dst_target,
+                if self.share.private_caps.direct_state_access {
+                    gl.copy_named_buffer_sub_data(
+                        src_buffer,
+                        dst_buffer,
                         data.src as _,
                         data.dst as _,
                         data.size as _,
                     );
-                }
+                } else {
+                    let is_index_buffer_only_element_dst =
+                        !self.share.private_caps.index_buffer_role_change
+                            && dst_target == glow::ELEMENT_ARRAY_BUFFER;
+
+                    let copy_src_target = glow::COPY_READ_BUFFER;
+                    // WebGL not allowed to copy data from other targets to element buffer and can't copy element data to other buffers
+                    let copy_dst_target = if is_index_buffer_only_element_dst {
+                        glow::ELEMENT_ARRAY_BUFFER
+                    } else {
+                        glow::COPY_WRITE_BUFFER
+                    };
 
-                gl.bind_buffer(copy_src_target, None);
+                    gl.bind_buffer(copy_src_target, Some(src_buffer));
+                    gl.bind_buffer(copy_dst_target, Some(dst_buffer));
 
-                if is_index_buffer_only_element_dst {
-                    gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, self.state.index_buffer);
-                } else {
-                    gl.bind_buffer(copy_dst_target, None);
+                    if is_index_buffer_only_element_dst {
+                        let mut buffer_data = vec![0; data.size as usize];
+                        gl.get_buffer_sub_data(copy_src_target, data.src as i32, &mut buffer_data);
+                        gl.buffer_sub_data_u8_slice(copy_dst_target, data.dst as i32, &buffer_data);
+                    } else {
+                        gl.copy_buffer_sub_data(
+                            copy_src_target,
+                            copy_dst_target,
+                            data.src as _,
+                            data.dst as _,
+                            data.size as _,
+                        );
+                    }
+
+                    gl.bind_buffer(copy_src_target, None);
+
+                    if is_index_buffer_only_element_dst {
+                        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, self.state.index_buffer);
+                    } else {
+                        gl.bind_buffer(copy_dst_target, None);
+                    }
                 }
             },
             com::Command::CopyBufferToTexture {
@@ -671,15 +887,55 @@ impl Queue {
                 pixel_type,
                 ref data,
             } => unsafe {
-                // TODO: Fix active texture
-                assert_eq!(data.image_offset.z, 0);
-
                 let gl = &self.share.context;
+                let dsa = self.share.private_caps.direct_state_access;
 
-                gl.active_texture(glow::TEXTURE0);
+                if !dsa {
+                    gl.active_texture(glow::TEXTURE0);
+                }
                 gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(src_buffer));
+                // A zero buffer width or height means tightly packed, as in GL.
+                gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, data.buffer_width as i32);
+                gl.pixel_store_i32(glow::UNPACK_IMAGE_HEIGHT, data.buffer_height as i32);
 
                 match texture_target {
+                    glow::TEXTURE_2D if dsa => {
+                        gl.texture_sub_image_2d(
+                            dst_texture,
+                            data.image_layers.level as _,
+                            data.image_offset.x,
+                            data.image_offset.y,
+                            data.image_extent.width as _,
+                            data.image_extent.height as _,
+                            texture_format,
+                            pixel_type,
+                            glow::PixelUnpackData::BufferOffset(data.buffer_offset as u32),
+                        );
+                    }
+                    glow::TEXTURE_2D_ARRAY | glow::TEXTURE_3D if dsa => {
+                        let (z, depth) = if texture_target == glow::TEXTURE_3D {
+                            (data.image_offset.z, data.image_extent.depth as i32)
+                        } else {
+                            (
+                                data.image_layers.layers.start as i32,
+                                data.image_layers.layers.end as i32
+                                    - data.image_layers.layers.start as i32,
+                            )
+                        };
+                        gl.texture_sub_image_3d(
+                            dst_texture,
+                            data.image_layers.level as _,
+                            data.image_offset.x,
+                            data.image_offset.y,
+                            z,
+                            data.image_extent.width as _,
+                            data.image_extent.height as _,
+                            depth,
+                            texture_format,
+                            pixel_type,
+                            glow::PixelUnpackData::BufferOffset(data.buffer_offset as u32),
+                        );
+                    }
                     glow::TEXTURE_2D => {
                         gl.bind_texture(glow::TEXTURE_2D, Some(dst_texture));
                         gl.tex_sub_image_2d(
@@ -711,10 +967,134 @@ impl Queue {
                             glow::PixelUnpackData::BufferOffset(data.buffer_offset as u32),
                         );
                     }
+                    glow::TEXTURE_3D => {
+                        gl.bind_texture(glow::TEXTURE_3D, Some(dst_texture));
+                        gl.tex_sub_image_3d(
+                            glow::TEXTURE_3D,
+                            data.image_layers.level as _,
+                            data.image_offset.x,
+                            data.image_offset.y,
+                            data.image_offset.z,
+                            data.image_extent.width as _,
+                            data.image_extent.height as _,
+                            data.image_extent.depth as _,
+                            texture_format,
+                            pixel_type,
+                            glow::PixelUnpackData::BufferOffset(data.buffer_offset as u32),
+                        );
+                    }
                     _ => unimplemented!(),
                 }
 
+                gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+                gl.pixel_store_i32(glow::UNPACK_IMAGE_HEIGHT, 0);
                 gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, None);
+                if !dsa {
+                    self.state.invalidate_texture(0);
+                }
+            },
+            com::Command::CopyBufferToCompressedTexture {
+                src_buffer,
+                dst_texture,
+                texture_target,
+                texture_format,
+                block_size,
+                block_dim: (block_width, block_height),
+                ref data,
+            } => unsafe {
+                let gl = &self.share.context;
+                let (block_width, block_height) = (block_width as u32, block_height as u32);
+
+                // Compressed data is addressed in blocks, so the buffer pitches are
+                // rounded up to whole blocks, which also covers partial edge blocks.
+                let row_texels = if data.buffer_width == 0 {
+                    data.image_extent.width
+                } else {
+                    data.buffer_width
+                };
+                let image_rows = if data.buffer_height == 0 {
+                    data.image_extent.height
+                } else {
+                    data.buffer_height
+                };
+                let row_size =
+                    (data.image_extent.width + block_width - 1) / block_width * block_size;
+                let row_pitch = (row_texels + block_width - 1) / block_width * block_size;
+                let slice_pitch = (image_rows + block_height - 1) / block_height * row_pitch;
+                let block_rows = (data.image_extent.height + block_height - 1) / block_height;
+
+                let (first_slice, num_slices) = if texture_target == glow::TEXTURE_3D {
+                    (data.image_offset.z as u32, data.image_extent.depth)
+                } else {
+                    (
+                        data.image_layers.layers.start as u32,
+                        (data.image_layers.layers.end - data.image_layers.layers.start) as u32,
+                    )
+                };
+
+                // Tightly packed rows are uploaded at once, others one block row at a time.
+                let rows = if row_pitch == row_size {
+                    vec![(0, data.image_extent.height, block_rows * row_size)]
+                } else {
+                    (0..block_rows)
+                        .map(|row| {
+                            let y = row * block_height;
+                            (y, block_height.min(data.image_extent.height - y), row_size)
+                        })
+                        .collect()
+                };
+
+                gl.active_texture(glow::TEXTURE0);
+                gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(src_buffer));
+                gl.bind_texture(texture_target, Some(dst_texture));
+
+                for i in 0..num_slices {
+                    let slice_offset = data.buffer_offset as u32 + i * slice_pitch;
+                    for &(y, height, size) in &rows {
+                        let offset = slice_offset + y / block_height * row_pitch;
+                        let range = offset..offset + size;
+                        match texture_target {
+                            glow::TEXTURE_2D | glow::TEXTURE_CUBE_MAP => {
+                                let target = if texture_target == glow::TEXTURE_CUBE_MAP {
+                                    glow::TEXTURE_CUBE_MAP_POSITIVE_X + first_slice + i
+                                } else {
+                                    glow::TEXTURE_2D
+                                };
+                                gl.compressed_tex_sub_image_2d(
+                                    target,
+                                    data.image_layers.level as _,
+                                    data.image_offset.x,
+                                    data.image_offset.y + y as i32,
+                                    data.image_extent.width as _,
+                                    height as _,
+                                    texture_format,
+                                    range,
+                                );
+                            }
+                            glow::TEXTURE_2D_ARRAY | glow::TEXTURE_3D => {
+                                gl.compressed_tex_sub_image_3d(
+                                    texture_target,
+                                    data.image_layers.level as _,
+                                    data.image_offset.x,
+                                    data.image_offset.y + y as i32,
+                                    (first_slice + i) as i32,
+                                    data.image_extent.width as _,
+                                    height as _,
+                                    1,
+                                    texture_format,
+                                    range,
+                                );
+                            }
+                            _ => log::error!(
+                                "Compressed uploads to {:#x} are not supported",
+                                texture_target
+                            ),
+                        }
+                    }
+                }
+
+                gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, None);
+                self.state.invalidate_texture(0);
             },
             com::Command::CopyBufferToRenderbuffer(..) => {
                 log::error!("CopyBufferToRenderbuffer is not implemented");
@@ -724,14 +1104,15 @@ impl Queue {
                 texture_target,
                 texture_format,
                 pixel_type,
+                texel_size,
                 dst_buffer,
                 ref data,
             } => {
-                if self.share.private_caps.get_tex_image {
-                    // TODO: Fix active texture
+                if self.share.private_caps.get_tex_image
+                    && texture_target == glow::TEXTURE_2D
+                    && data.image_offset == hal::image::Offset::ZERO
+                {
                     // TODO: handle partial copies gracefully
-                    assert_eq!(data.image_offset, hal::image::Offset { x: 0, y: 0, z: 0 });
-                    assert_eq!(texture_target, glow::TEXTURE_2D);
                     let gl = &self.share.context;
                     unsafe {
                         gl.active_texture(glow::TEXTURE0);
@@ -740,24 +1121,124 @@ impl Queue {
                         gl.get_tex_image(
                             glow::TEXTURE_2D,
                             data.image_layers.level as _,
-                            //data.image_offset.x,
-                            //data.image_offset.y,
-                            //data.image_extent.width as _,
-                            //data.image_extent.height as _,
                             texture_format,
                             pixel_type,
                             glow::PixelPackData::BufferOffset(data.buffer_offset as u32),
                         );
                         gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
                     }
+                    self.state.invalidate_texture(0);
+                } else if self.share.private_caps.framebuffer {
+                    // Attach each source layer (or depth slice) to a temporary
+                    // framebuffer and read it back into the pixel pack buffer.
+                    let (first_slice, num_slices) = if texture_target == glow::TEXTURE_3D {
+                        (data.image_offset.z as u32, data.image_extent.depth)
+                    } else {
+                        (
+                            data.image_layers.layers.start as u32,
+                            (data.image_layers.layers.end - data.image_layers.layers.start) as u32,
+                        )
+                    };
+                    let row_length = if data.buffer_width == 0 {
+                        data.image_extent.width
+                    } else {
+                        data.buffer_width
+                    };
+                    let image_height = if data.buffer_height == 0 {
+                        data.image_extent.height
+                    } else {
+                        data.buffer_height
+                    };
+                    let slice_pitch = row_length * image_height * texel_size;
+
+                    let gl = &self.share.context;
+                    unsafe {
+                        let fbo = gl.create_framebuffer().unwrap();
+                        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
+                        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(dst_buffer));
+                        gl.pixel_store_i32(glow::PACK_ROW_LENGTH, row_length as i32);
+                        gl.pixel_store_i32(glow::PACK_IMAGE_HEIGHT, image_height as i32);
+
+                        for i in 0..num_slices {
+                            match texture_target {
+                                glow::TEXTURE_2D => gl.framebuffer_texture_2d(
+                                    glow::READ_FRAMEBUFFER,
+                                    glow::COLOR_ATTACHMENT0,
+                                    glow::TEXTURE_2D,
+                                    Some(src_texture),
+                                    data.image_layers.level as _,
+                                ),
+                                _ => gl.framebuffer_texture_layer(
+                                    glow::READ_FRAMEBUFFER,
+                                    glow::COLOR_ATTACHMENT0,
+                                    Some(src_texture),
+                                    data.image_layers.level as _,
+                                    (first_slice + i) as _,
+                                ),
+                            }
+                            gl.read_pixels(
+                                data.image_offset.x,
+                                data.image_offset.y,
+                                data.image_extent.width as _,
+                                data.image_extent.height as _,
+                                texture_format,
+                                pixel_type,
+                                glow::PixelPackData::BufferOffset(
+                                    data.buffer_offset as u32 + i * slice_pitch,
+                                ),
+                            );
+                        }
+
+                        gl.pixel_store_i32(glow::PACK_IMAGE_HEIGHT, 0);
+                        gl.pixel_store_i32(glow::PACK_ROW_LENGTH, 0);
+                        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+
+                        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+                        gl.delete_framebuffer(fbo);
+                    }
                 } else {
-                    //TODO: use FBO
-                    log::error!("CopyTextureToBuffer is not implemented on GLES");
+                    log::error!("CopyTextureToBuffer requires framebuffer support");
                 }
             }
-            com::Command::CopyRenderbufferToBuffer(..) => {
-                //TODO: use FBO
-                log::error!("CopyRenderbufferToBuffer is not implemented");
+            com::Command::CopyRenderbufferToBuffer {
+                src_renderbuffer,
+                texture_format,
+                pixel_type,
+                dst_buffer,
+                ref data,
+            } => {
+                if self.share.private_caps.framebuffer {
+                    let gl = &self.share.context;
+                    unsafe {
+                        let fbo = gl.create_framebuffer().unwrap();
+                        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
+                        gl.framebuffer_renderbuffer(
+                            glow::READ_FRAMEBUFFER,
+                            glow::COLOR_ATTACHMENT0,
+                            glow::RENDERBUFFER,
+                            Some(src_renderbuffer),
+                        );
+
+                        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(dst_buffer));
+                        gl.pixel_store_i32(glow::PACK_ROW_LENGTH, data.buffer_width as i32);
+                        gl.read_pixels(
+                            data.image_offset.x,
+                            data.image_offset.y,
+                            data.image_extent.width as _,
+                            data.image_extent.height as _,
+                            texture_format,
+                            pixel_type,
+                            glow::PixelPackData::BufferOffset(data.buffer_offset as u32),
+                        );
+                        gl.pixel_store_i32(glow::PACK_ROW_LENGTH, 0);
+                        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+
+                        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+                        gl.delete_framebuffer(fbo);
+                    }
+                } else {
+                    log::error!("CopyRenderbufferToBuffer requires framebuffer support");
+                }
             }
             com::Command::CopyImageToTexture(..) => {
                 //TODO: use FBO
@@ -782,6 +1263,7 @@ impl Queue {
                     native::ImageType::Renderbuffer {
                         raw: src_renderbuffer,
                         format: src_format,
+                        ..
                     } => {
                         if src_format != dst_format {
                             unimplemented!()
@@ -827,19 +1309,189 @@ impl Queue {
                     }
                 }
             }
+            com::Command::BlitImage {
+                src_image,
+                dst_image,
+                filter,
+                ref data,
+            } => {
+                use hal::format::Aspects;
+
+                let aspects = data.src_subresource.aspects & data.dst_subresource.aspects;
+                let (attachment, mask) = if aspects.contains(Aspects::COLOR) {
+                    (glow::COLOR_ATTACHMENT0, glow::COLOR_BUFFER_BIT)
+                } else if aspects == Aspects::DEPTH | Aspects::STENCIL {
+                    (
+                        glow::DEPTH_STENCIL_ATTACHMENT,
+                        glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT,
+                    )
+                } else if aspects == Aspects::DEPTH {
+                    (glow::DEPTH_ATTACHMENT, glow::DEPTH_BUFFER_BIT)
+                } else {
+                    (glow::STENCIL_ATTACHMENT, glow::STENCIL_BUFFER_BIT)
+                };
+                // Depth and stencil can't be filtered linearly.
+                let filter = if mask == glow::COLOR_BUFFER_BIT {
+                    filter
+                } else {
+                    glow::NEAREST
+                };
+
+                let (src_fbo, dst_fbo) = unsafe {
+                    let gl = &self.share.context;
+                    (
+                        gl.create_framebuffer().unwrap(),
+                        gl.create_framebuffer().unwrap(),
+                    )
+                };
+
+                // 3D images blit their depth slices, which are attached like layers.
+                let slices = |image: native::ImageType,
+                              layers: &Range<hal::image::Layer>,
+                              bounds: &Range<hal::image::Offset>| {
+                    match image {
+                        native::ImageType::Texture {
+                            target: glow::TEXTURE_3D,
+                            ..
+                        } => {
+                            let (start, end) = (bounds.start.z, bounds.end.z);
+                            start.min(end) as u32..start.max(end) as u32
+                        }
+                        _ => layers.start as u32..layers.end as u32,
+                    }
+                };
+                let src_slices = slices(src_image, &data.src_subresource.layers, &data.src_bounds);
+                let dst_slices = slices(dst_image, &data.dst_subresource.layers, &data.dst_bounds);
+                let src_count = src_slices.end - src_slices.start;
+                let dst_count = dst_slices.end - dst_slices.start;
+                for (i, dst_layer) in dst_slices.enumerate() {
+                    // Scaling along z picks the nearest source slice.
+                    let src_layer = src_slices.start + i as u32 * src_count / dst_count.max(1);
+                    unsafe {
+                        self.share
+                            .context
+                            .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src_fbo));
+                    }
+                    self.bind_image_level(
+                        glow::READ_FRAMEBUFFER,
+                        attachment,
+                        src_image,
+                        data.src_subresource.level,
+                        src_layer as hal::image::Layer,
+                    );
+                    unsafe {
+                        self.share
+                            .context
+                            .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(dst_fbo));
+                    }
+                    self.bind_image_level(
+                        glow::DRAW_FRAMEBUFFER,
+                        attachment,
+                        dst_image,
+                        data.dst_subresource.level,
+                        dst_layer as hal::image::Layer,
+                    );
+
+                    let gl = &self.share.context;
+                    unsafe {
+                        gl.blit_framebuffer(
+                            data.src_bounds.start.x,
+                            data.src_bounds.start.y,
+                            data.src_bounds.end.x,
+                            data.src_bounds.end.y,
+                            data.dst_bounds.start.x,
+                            data.dst_bounds.start.y,
+                            data.dst_bounds.end.x,
+                            data.dst_bounds.end.y,
+                            mask,
+                            filter,
+                        );
+                    }
+                }
+
+                let gl = &self.share.context;
+                unsafe {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    gl.delete_framebuffer(src_fbo);
+                    gl.delete_framebuffer(dst_fbo);
+                }
+            }
+            com::Command::GenerateMipmap(texture, target, level_count) => unsafe {
+                let gl = &self.share.context;
+                gl.active_texture(glow::TEXTURE0);
+                gl.bind_texture(target, Some(texture));
+                // Views may have restricted the level range, generate all of them.
+                gl.tex_parameter_i32(target, glow::TEXTURE_BASE_LEVEL, 0);
+                gl.tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, level_count as i32 - 1);
+                gl.generate_mipmap(target);
+                self.state.invalidate_texture(0);
+            },
             com::Command::BindBufferRange(target, index, buffer, offset, size) => unsafe {
                 let gl = &self.share.context;
                 gl.bind_buffer_range(target, index, Some(buffer), offset, size);
             },
-            com::Command::BindTexture(index, texture, textype) => unsafe {
+            com::Command::BindTexture(index, texture, textype) => {
+                let binding = Some((textype, texture));
+                match self.state.textures.get_mut(index as usize) {
+                    Some(cached) if *cached == binding => {}
+                    cached => {
+                        let gl = &self.share.context;
+                        unsafe {
+                            gl.active_texture(glow::TEXTURE0 + index);
+                            gl.bind_texture(textype, Some(texture));
+                        }
+                        if let Some(cached) = cached {
+                            *cached = binding;
+                        }
+                    }
+                }
+            }
+            com::Command::BindImage {
+                unit,
+                texture,
+                level,
+                layer,
+                access,
+                format,
+            } => {
+                if !self
+                    .share
+                    .legacy_features
+                    .contains(LegacyFeatures::IMAGE_LOAD_STORE)
+                {
+                    log::error!("Storage images are not supported");
+                } else {
+                    unsafe {
+                        self.share.context.bind_image_texture(
+                            unit,
+                            texture,
+                            level as i32,
+                            layer.is_none(),
+                            layer.unwrap_or(0) as i32,
+                            access,
+                            format,
+                        );
+                    }
+                }
+            }
+            com::Command::SetTextureLevels(index, textype, ref levels) => unsafe {
+                // Restrict sampling to the mip range of the bound view.
                 let gl = &self.share.context;
                 gl.active_texture(glow::TEXTURE0 + index);
-                gl.bind_texture(textype, Some(texture));
-            },
-            com::Command::BindSampler(index, sampler) => unsafe {
-                let gl = &self.share.context;
-                gl.bind_sampler(index, Some(sampler));
+                gl.tex_parameter_i32(textype, glow::TEXTURE_BASE_LEVEL, levels.start as i32);
+                gl.tex_parameter_i32(textype, glow::TEXTURE_MAX_LEVEL, levels.end as i32 - 1);
             },
+            com::Command::BindSampler(index, sampler) => {
+                match self.state.samplers.get_mut(index as usize) {
+                    Some(cached) if *cached == Some(sampler) => {}
+                    cached => {
+                        unsafe { self.share.context.bind_sampler(index, Some(sampler)) };
+                        if let Some(cached) = cached {
+                            *cached = Some(sampler);
+                        }
+                    }
+                }
+            }
             com::Command::SetTextureSamplerSettings(index, textype, ref sinfo) => unsafe {
                 let gl = &self.share.context;
                 gl.active_texture(glow::TEXTURE0 + index);
@@ -999,7 +1651,7 @@ impl Queue {
                     }
                 }
 
-                let (_gl_draw, gl_offset) = match rasterizer.polygon_mode {
+                let (gl_draw, gl_offset) = match rasterizer.polygon_mode {
                     Point => (glow::POINT, glow::POLYGON_OFFSET_POINT),
                     Line => (glow::LINE, glow::POLYGON_OFFSET_LINE),
                     Fill => (glow::FILL, glow::POLYGON_OFFSET_FILL),
@@ -1012,50 +1664,96 @@ impl Queue {
                     }
                 }
 
-                //TODO: this is not available in GLES
-                //unsafe { gl.polygon_mode(glow::FRONT_AND_BACK, gl_draw) };
+                // Not available in GLES, where only filling is supported.
+                if self
+                    .share
+                    .supported_features
+                    .contains(hal::Features::NON_FILL_POLYGON_MODE)
+                {
+                    unsafe { gl.polygon_mode(glow::FRONT_AND_BACK, gl_draw) };
+                }
+
+                if self
+                    .share
+                    .supported_features
+                    .contains(hal::Features::DEPTH_CLAMP)
+                {
+                    match rasterizer.depth_clamping {
+                        true => unsafe { gl.enable(glow::DEPTH_CLAMP) },
+                        false => unsafe { gl.disable(glow::DEPTH_CLAMP) },
+                    }
+                }
 
                 match rasterizer.depth_bias {
                     Some(hal::pso::State::Static(bias)) => unsafe {
                         gl.enable(gl_offset);
                         gl.polygon_offset(bias.slope_factor as _, bias.const_factor as _);
                     },
-                    _ => unsafe { gl.disable(gl_offset) },
+                    // The offset itself comes from `set_depth_bias`.
+                    Some(hal::pso::State::Dynamic) => unsafe { gl.enable(gl_offset) },
+                    None => unsafe { gl.disable(gl_offset) },
                 }
+            }
+            com::Command::BindMultisampling(ref multisampling) => {
+                let gl = &self.share.context;
 
                 if !self.share.info.version.is_embedded {
-                    match false {
-                        //TODO
-                        true => unsafe { gl.enable(glow::MULTISAMPLE) },
-                        false => unsafe { gl.disable(glow::MULTISAMPLE) },
+                    match multisampling {
+                        Some(_) => unsafe { gl.enable(glow::MULTISAMPLE) },
+                        None => unsafe { gl.disable(glow::MULTISAMPLE) },
+                    }
+                }
+
+                let (alpha_coverage, sample_shading) = match multisampling {
+                    Some(ms) => (ms.alpha_coverage, ms.sample_shading),
+                    None => (false, None),
+                };
+                match alpha_coverage {
+                    true => unsafe { gl.enable(glow::SAMPLE_ALPHA_TO_COVERAGE) },
+                    false => unsafe { gl.disable(glow::SAMPLE_ALPHA_TO_COVERAGE) },
+                }
+                if self
+                    .share
+                    .supported_features
+                    .contains(hal::Features::SAMPLE_RATE_SHADING)
+                {
+                    match sample_shading {
+                        Some(min_sample_shading) => unsafe {
+                            gl.enable(glow::SAMPLE_SHADING);
+                            gl.min_sample_shading(min_sample_shading);
+                        },
+                        None => unsafe { gl.disable(glow::SAMPLE_SHADING) },
                     }
                 }
             }
             com::Command::BindDepth(depth_fun) => {
                 use hal::pso::Comparison::*;
 
-                let gl = &self.share.context;
-
-                match depth_fun {
-                    Some(depth_fun) => unsafe {
-                        gl.enable(glow::DEPTH_TEST);
-
-                        let cmp = match depth_fun {
-                            Never => glow::NEVER,
-                            Less => glow::LESS,
-                            LessEqual => glow::LEQUAL,
-                            Equal => glow::EQUAL,
-                            GreaterEqual => glow::GEQUAL,
-                            Greater => glow::GREATER,
-                            NotEqual => glow::NOTEQUAL,
-                            Always => glow::ALWAYS,
-                        };
+                if self.state.depth_func != Some(depth_fun) {
+                    let gl = &self.share.context;
 
-                        gl.depth_func(cmp);
-                    },
-                    None => unsafe {
-                        gl.disable(glow::DEPTH_TEST);
-                    },
+                    match depth_fun {
+                        Some(depth_fun) => unsafe {
+                            gl.enable(glow::DEPTH_TEST);
+
+                            let cmp = match depth_fun {
+                                Never => glow::NEVER,
+                                Less => glow::LESS,
+                                LessEqual => glow::LEQUAL,
+                                Equal => glow::EQUAL,
+                                GreaterEqual => glow::GEQUAL,
+                                Greater => glow::GREATER,
+                                NotEqual => glow::NOTEQUAL,
+                                Always => glow::ALWAYS,
+                            };
+
+                            gl.depth_func(cmp);
+                        },
+                        None => unsafe {
+                            gl.disable(glow::DEPTH_TEST);
+                        },
+                    }
+                    self.state.depth_func = Some(depth_fun);
                 }
             }
             com::Command::SetColorMask(slot, mask) => unsafe {
@@ -1103,6 +1801,38 @@ impl Queue {
                     }
                 }
             }
+            com::Command::InsertDebugMarker(name_ptr) => {
+                let gl = &self.share.context;
+                if gl.supports_debug() {
+                    let name = str::from_utf8(Self::get_raw(data_buf, name_ptr)).unwrap();
+                    unsafe {
+                        gl.debug_message_insert(
+                            glow::DEBUG_SOURCE_APPLICATION,
+                            glow::DEBUG_TYPE_MARKER,
+                            0,
+                            glow::DEBUG_SEVERITY_NOTIFICATION,
+                            name,
+                        );
+                    }
+                }
+            }
+            com::Command::PushDebugGroup(name_ptr) => {
+                let gl = &self.share.context;
+                if gl.supports_debug() {
+                    let name = str::from_utf8(Self::get_raw(data_buf, name_ptr)).unwrap();
+                    unsafe {
+                        gl.push_debug_group(glow::DEBUG_SOURCE_APPLICATION, 0, name);
+                    }
+                }
+            }
+            com::Command::PopDebugGroup => {
+                let gl = &self.share.context;
+                if gl.supports_debug() {
+                    unsafe {
+                        gl.pop_debug_group();
+                    }
+                }
+            }
         }
         if let Err(err) = self.share.check() {
             panic!("Error {:?} executing command: {:?}", err, cmd)
@@ -1147,6 +1877,14 @@ impl hal::queue::Queue<Backend> for Queue {
             }
         }
 
+        // Device writes to non-coherent persistent mappings only become
+        // visible to the host after this barrier and a completed fence.
+        if self.share.private_caps.buffer_storage && self.share.private_caps.memory_barrier {
+            self.share
+                .context
+                .memory_barrier(glow::CLIENT_MAPPED_BUFFER_BARRIER_BIT);
+        }
+
         if let Some(fence) = fence {
             *fence = if self.share.private_caps.sync {
                 native::Fence::Pending(
@@ -1168,13 +1906,15 @@ impl hal::queue::Queue<Backend> for Queue {
         image: native::SwapchainImage,
         _wait_semaphore: Option<&mut native::Semaphore>,
     ) -> Result<Option<hal::window::Suboptimal>, hal::window::PresentError> {
+        self.share.check_context_lost()?;
         surface.present(image, &self.share.context)
     }
 
-    fn wait_idle(&mut self) -> Result<(), hal::device::OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), hal::device::WaitError> {
         unsafe {
             self.share.context.finish();
         }
+        self.share.check_context_lost()?;
         Ok(())
     }
 
@@ -13,7 +13,10 @@ pub struct Swapchain {
     /// Extent because the window lies
     extent: w::Extent2D,
     format: native::TextureFormat,
+    pixel_type: native::DataType,
     channel: hal::format::ChannelType,
+    /// Value passed to `eglSwapInterval` before each swap.
+    swap_interval: i32,
 }
 
 #[derive(Debug)]
@@ -41,6 +44,8 @@ unsafe impl Sync for Instance {}
 
 const EGL_PLATFORM_WAYLAND_KHR: u32 = 0x31D8;
 const EGL_PLATFORM_X11_KHR: u32 = 0x31D5;
+const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT: i32 = 0x3138;
+const EGL_LOSE_CONTEXT_ON_RESET_EXT: i32 = 0x31BF;
 
 type XOpenDisplayFun =
     unsafe extern "system" fn(display_name: *const raw::c_char) -> *mut raw::c_void;
@@ -197,6 +202,11 @@ impl Inner {
             context_attributes.push(egl::CONTEXT_OPENGL_DEBUG);
             context_attributes.push(egl::TRUE as _);
         }
+        if display_extensions.contains("EGL_EXT_create_context_robustness") {
+            // Required for the context reset status to report device loss.
+            context_attributes.push(EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT);
+            context_attributes.push(EGL_LOSE_CONTEXT_ON_RESET_EXT);
+        }
         context_attributes.push(egl::NONE as _);
         let context = match egl.create_context(display, config, None, &context_attributes) {
             Ok(context) => context,
@@ -324,16 +334,16 @@ impl hal::Instance<crate::Backend> for Instance {
             )
             .unwrap();
 
-        let context = unsafe {
-            glow::Context::from_loader_function(|name| {
-                inner
-                    .egl
-                    .get_proc_address(name)
-                    .map_or(ptr::null(), |p| p as *const _)
-            })
+        let loader = |name: &str| {
+            inner
+                .egl
+                .get_proc_address(name)
+                .map_or(ptr::null(), |p| p as *const _)
         };
+        let context = unsafe { glow::Context::from_loader_function(loader) };
+        let ext = crate::ext::ExtFns::load(loader);
         // Create physical device
-        vec![PhysicalDevice::new_adapter(context)]
+        vec![PhysicalDevice::new_adapter(context, ext)]
     }
 
     #[cfg_attr(target_os = "macos", allow(unused, unused_mut, unreachable_code))]
@@ -533,6 +543,9 @@ impl w::PresentationSurface<crate::Backend> for Surface {
         device: &crate::Device,
         config: w::SwapchainConfig,
     ) -> Result<(), w::SwapchainError> {
+        if config.color_space != w::ColorSpace::SRGB_NONLINEAR {
+            return Err(w::SwapchainError::UnsupportedColorSpace);
+        }
         self.unconfigure_swapchain(device);
 
         if let Some(window) = self.wl_window {
@@ -548,7 +561,14 @@ impl w::PresentationSurface<crate::Backend> for Surface {
             );
         }
 
-        let desc = conv::describe_format(config.format).unwrap();
+        let desc = match conv::describe_format(config.format, device.share.info.version.is_embedded)
+        {
+            Some(desc) => desc,
+            None => {
+                log::error!("Swapchain format {:?} is not supported", config.format);
+                return Err(w::SwapchainError::Unknown);
+            }
+        };
 
         let gl = &device.share.context;
         let renderbuffer = gl.create_renderbuffer().unwrap();
@@ -574,8 +594,13 @@ impl w::PresentationSurface<crate::Backend> for Surface {
             renderbuffer,
             framebuffer,
             extent: config.extent,
-            format: desc.tex_internal,
+            format: desc.tex_external,
+            pixel_type: desc.data_type,
             channel: config.format.base_format().1,
+            swap_interval: match config.present_mode {
+                w::PresentMode::IMMEDIATE => 0,
+                _ => 1,
+            },
         });
 
         Ok(())
@@ -594,8 +619,13 @@ impl w::PresentationSurface<crate::Backend> for Surface {
         _timeout_ns: u64,
     ) -> Result<(Self::SwapchainImage, Option<w::Suboptimal>), w::AcquireError> {
         let sc = self.swapchain.as_ref().unwrap();
-        let sc_image =
-            native::SwapchainImage::new(sc.renderbuffer, sc.format, sc.extent, sc.channel);
+        let sc_image = native::SwapchainImage::new(
+            sc.renderbuffer,
+            sc.format,
+            sc.pixel_type,
+            sc.extent,
+            sc.channel,
+        );
         Ok((sc_image, None))
     }
 }
@@ -607,8 +637,9 @@ impl w::Surface<crate::Backend> for Surface {
 
     fn capabilities(&self, _physical_device: &PhysicalDevice) -> w::SurfaceCapabilities {
         w::SurfaceCapabilities {
-            present_modes: w::PresentMode::FIFO,                  //TODO
+            present_modes: w::PresentMode::FIFO | w::PresentMode::IMMEDIATE,
             composite_alpha_modes: w::CompositeAlphaMode::OPAQUE, //TODO
+            color_spaces: w::ColorSpace::SRGB_NONLINEAR,
             image_count: 2..=2,
             current_extent: None,
             extents: w::Extent2D {
@@ -619,16 +650,21 @@ impl w::Surface<crate::Backend> for Surface {
                 height: 4096,
             },
             max_image_layers: 1,
-            usage: image::Usage::COLOR_ATTACHMENT,
+            usage: image::Usage::COLOR_ATTACHMENT | image::Usage::TRANSFER_SRC,
         }
     }
 
     fn supported_formats(
         &self,
-        _physical_device: &PhysicalDevice,
+        physical_device: &PhysicalDevice,
     ) -> Option<Vec<hal::format::Format>> {
         use hal::format::Format;
-        Some(vec![Format::Rgba8Srgb, Format::Bgra8Srgb])
+        // GLES has no sRGB variant of BGRA.
+        if physical_device.0.info.version.is_embedded {
+            Some(vec![Format::Rgba8Srgb])
+        } else {
+            Some(vec![Format::Rgba8Srgb, Format::Bgra8Srgb])
+        }
     }
 }
 
@@ -648,6 +684,9 @@ impl Surface {
                 Some(self.context),
             )
             .unwrap();
+        if let Err(e) = self.egl.swap_interval(self.display, sc.swap_interval) {
+            log::warn!("Error in swap_interval: {:?}", e);
+        }
         gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
         gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(sc.framebuffer));
         gl.blit_framebuffer(
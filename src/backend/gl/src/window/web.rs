@@ -12,6 +12,7 @@ pub struct Swapchain {
     pub(crate) extent: window::Extent2D,
     pub(crate) channel: f::ChannelType,
     pub(crate) raw_format: native::TextureFormat,
+    pub(crate) pixel_type: native::DataType,
     pub(crate) framebuffer: native::RawFramebuffer,
 }
 
@@ -59,7 +60,8 @@ impl hal::Instance<B> for Instance {
             None => return Vec::new(),
         };
 
-        let adapter = PhysicalDevice::new_adapter(context);
+        // The extra entry points can't be loaded on WebGL.
+        let adapter = PhysicalDevice::new_adapter(context, crate::ext::ExtFns::default());
         vec![adapter]
     }
 
@@ -166,6 +168,7 @@ impl window::Surface<B> for Surface {
         window::SurfaceCapabilities {
             present_modes: window::PresentMode::FIFO, //TODO
             composite_alpha_modes: window::CompositeAlphaMode::OPAQUE, //TODO
+            color_spaces: window::ColorSpace::SRGB_NONLINEAR,
             image_count: 1..=1,
             current_extent: Some(extent),
             extents: extent..=extent,
@@ -187,6 +190,9 @@ impl window::PresentationSurface<B> for Surface {
         device: &Device,
         config: window::SwapchainConfig,
     ) -> Result<(), window::SwapchainError> {
+        if config.color_space != window::ColorSpace::SRGB_NONLINEAR {
+            return Err(window::SwapchainError::UnsupportedColorSpace);
+        }
         let gl = &device.share.context;
 
         if let Some(swapchain) = self.swapchain.take() {
@@ -198,7 +204,14 @@ impl window::PresentationSurface<B> for Surface {
             self.renderbuffer = Some(gl.create_renderbuffer().unwrap());
         }
 
-        let desc = conv::describe_format(config.format).unwrap();
+        let desc = match conv::describe_format(config.format, device.share.info.version.is_embedded)
+        {
+            Some(desc) => desc,
+            None => {
+                log::error!("Swapchain format {:?} is not supported", config.format);
+                return Err(window::SwapchainError::Unknown);
+            }
+        };
         gl.bind_renderbuffer(glow::RENDERBUFFER, self.renderbuffer);
         gl.renderbuffer_storage(
             glow::RENDERBUFFER,
@@ -219,6 +232,7 @@ impl window::PresentationSurface<B> for Surface {
             extent: config.extent,
             channel: config.format.base_format().1,
             raw_format: desc.tex_external,
+            pixel_type: desc.data_type,
             framebuffer,
         });
         Ok(())
@@ -242,6 +256,7 @@ impl window::PresentationSurface<B> for Surface {
         let swapchain_image = native::SwapchainImage::new(
             self.renderbuffer.unwrap(),
             sc.raw_format,
+            sc.pixel_type,
             sc.extent,
             sc.channel,
         );
@@ -8,7 +8,7 @@ use crate::{
 
 use hal::{
     buffer, command as com,
-    device::OutOfMemory,
+    device::WaitError,
     format::{Aspects, FormatDesc},
     image as i, memory,
     pass::AttachmentLoadOp,
@@ -2114,6 +2114,9 @@ where
                 );
             }
         }
+        Cmd::GenerateMipmaps { texture } => {
+            encoder.generate_mipmaps(texture.as_native());
+        }
     }
 }
 
@@ -2515,7 +2518,7 @@ impl hal::queue::Queue<Backend> for Queue {
         Ok(None)
     }
 
-    fn wait_idle(&mut self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), WaitError> {
         QueueInner::wait_idle(&self.shared.queue);
         Ok(())
     }
@@ -3469,6 +3472,16 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         retained_textures.extend(dst_cubish);
     }
 
+    unsafe fn generate_mipmaps(&mut self, image: &native::Image) {
+        let command = soft::BlitCommand::GenerateMipmaps {
+            texture: AsNative::from(image.like.as_texture()),
+        };
+        self.inner
+            .borrow_mut()
+            .sink()
+            .blit_commands(iter::once(command));
+    }
+
     unsafe fn bind_index_buffer(
         &mut self,
         buffer: &native::Buffer,
@@ -4930,6 +4943,20 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         // nothing to do, timestamps are unsupported on Metal
     }
 
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        _buffer: &native::Buffer,
+        _offset: buffer::Offset,
+        _inverted: bool,
+    ) {
+        // Metal has no predication, and the feature isn't exposed.
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
     unsafe fn push_graphics_constants(
         &mut self,
         layout: &native::PipelineLayout,
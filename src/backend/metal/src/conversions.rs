@@ -93,6 +93,8 @@ impl PrivateCapabilities {
             f::Etc2R8g8b8Srgb if self.format_eac_etc => ETC2_RGB8_sRGB,
             f::Etc2R8g8b8a1Unorm if self.format_eac_etc => ETC2_RGB8A1,
             f::Etc2R8g8b8a1Srgb if self.format_eac_etc => ETC2_RGB8A1_sRGB,
+            f::Etc2R8g8b8a8Unorm if self.format_eac_etc => EAC_RGBA8,
+            f::Etc2R8g8b8a8Srgb if self.format_eac_etc => EAC_RGBA8_sRGB,
             f::Astc4x4Unorm if self.format_astc => ASTC_4x4_LDR,
             f::Astc4x4Srgb if self.format_astc => ASTC_4x4_sRGB,
             f::Astc5x4Unorm if self.format_astc => ASTC_5x4_LDR,
@@ -2,7 +2,7 @@
 use crate::pipeline_cache;
 use crate::{
     command, conversions as conv, internal::Channel, native as n, AsNative, Backend, FastHashMap,
-    OnlineRecording, QueueFamily, ResourceIndex, Shared, VisibilityShared,
+    HeapStatistics, OnlineRecording, QueueFamily, ResourceIndex, Shared, VisibilityShared,
     MAX_BOUND_DESCRIPTOR_SETS, MAX_COLOR_ATTACHMENTS,
 };
 
@@ -423,7 +423,6 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             | F::DRAW_INDIRECT_FIRST_INSTANCE
             | F::DEPTH_CLAMP
             | F::SAMPLER_ANISOTROPY
-            | F::FORMAT_BC
             | F::PRECISE_OCCLUSION_QUERY
             | F::SHADER_STORAGE_BUFFER_ARRAY_DYNAMIC_INDEXING
             | F::VERTEX_STORES_AND_ATOMICS
@@ -434,6 +433,9 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
             | F::MUTABLE_UNNORMALIZED_SAMPLER
             | F::NDC_Y_UP;
 
+        features.set(F::FORMAT_BC, self.shared.private_caps.format_bc);
+        features.set(F::FORMAT_ETC2, self.shared.private_caps.format_eac_etc);
+        features.set(F::FORMAT_ASTC_LDR, self.shared.private_caps.format_astc);
         features.set(
             F::IMAGE_CUBE_ARRAY,
             self.shared.private_caps.texture_cube_array,
@@ -620,6 +622,11 @@ impl Device {
         self.shared.device.lock()
     }
 
+    /// Returns the statistics of the heaps backing private memory allocations.
+    pub fn heap_statistics(&self) -> HeapStatistics {
+        *self.shared.heap_statistics.lock()
+    }
+
     fn _is_heap_coherent(&self, heap: &n::MemoryHeap) -> bool {
         match *heap {
             n::MemoryHeap::Private => false,
@@ -2716,17 +2723,22 @@ impl hal::device::Device<Backend> for Device {
         let device = self.shared.device.lock();
         debug!("allocate_memory type {:?} of size {}", memory_type, size);
 
-        // Heaps cannot be used for CPU coherent resources
-        //TEMP: MacOS supports Private only, iOS and tvOS can do private/shared
-        let heap = if self.shared.private_caps.resource_heaps
-            && storage != MTLStorageMode::Shared
-            && false
+        // Heap memory can't be mapped, so only private memory is backed by heaps.
+        // Only placement heaps honor the offsets resources are bound at.
+        let heap = if self.shared.private_caps.placement_heaps && storage == MTLStorageMode::Private
         {
             let descriptor = metal::HeapDescriptor::new();
+            descriptor.set_heap_type(metal::MTLHeapType::Placement);
             descriptor.set_storage_mode(storage);
             descriptor.set_cpu_cache_mode(cache);
             descriptor.set_size(size);
+            // Resources placed in heaps are untracked by default, and
+            // `pipeline_barrier` doesn't insert any fences between them.
+            descriptor.set_hazard_tracking_mode(metal::MTLHazardTrackingMode::Tracked);
             let heap_raw = device.new_heap(&descriptor);
+            let mut stats = self.shared.heap_statistics.lock();
+            stats.heap_count += 1;
+            stats.heap_bytes += heap_raw.size();
             n::MemoryHeap::Native(heap_raw)
         } else if storage == MTLStorageMode::Private {
             n::MemoryHeap::Private
@@ -2743,8 +2755,16 @@ impl hal::device::Device<Backend> for Device {
     unsafe fn free_memory(&self, memory: n::Memory) {
         profiling::scope!("free_memory");
         debug!("free_memory of size {}", memory.size);
-        if let n::MemoryHeap::Public(_, ref cpu_buffer) = memory.heap {
-            debug!("\tbacked by cpu buffer {:?}", cpu_buffer.as_ptr());
+        match memory.heap {
+            n::MemoryHeap::Public(_, ref cpu_buffer) => {
+                debug!("\tbacked by cpu buffer {:?}", cpu_buffer.as_ptr());
+            }
+            n::MemoryHeap::Native(ref heap) => {
+                let mut stats = self.shared.heap_statistics.lock();
+                stats.heap_count -= 1;
+                stats.heap_bytes -= heap.size();
+            }
+            n::MemoryHeap::Private => {}
         }
     }
 
@@ -2821,10 +2841,13 @@ impl hal::device::Device<Backend> for Device {
                     heap.storage_mode(),
                     heap.cpu_cache_mode(),
                 );
-                let raw = heap.new_buffer(size, options).unwrap_or_else(|| {
-                    // TODO: disable hazard tracking?
-                    self.shared.device.lock().new_buffer(size, options)
-                });
+                let raw = match heap.new_buffer_with_offset(size, options, offset) {
+                    Some(raw) => raw,
+                    None => {
+                        error!("Unable to place buffer at offset {} in the heap", offset);
+                        return Err(d::BindError::OutOfBounds);
+                    }
+                };
                 raw.set_label(name);
                 n::Buffer::Bound {
                     raw,
@@ -3164,12 +3187,15 @@ impl hal::device::Device<Backend> for Device {
                         heap.cpu_cache_mode(),
                     );
                     descriptor.set_resource_options(resource_options);
-                    n::ImageLike::Texture(heap.new_texture(descriptor).unwrap_or_else(|| {
-                        // TODO: disable hazard tracking?
-                        let texture = self.shared.device.lock().new_texture(&descriptor);
-                        texture.set_label(name);
-                        texture
-                    }))
+                    let texture = match heap.new_texture_with_offset(descriptor, offset) {
+                        Some(texture) => texture,
+                        None => {
+                            error!("Unable to place texture at offset {} in the heap", offset);
+                            return Err(d::BindError::OutOfBounds);
+                        }
+                    };
+                    texture.set_label(name);
+                    n::ImageLike::Texture(texture)
                 }
                 n::MemoryHeap::Public(_memory_type, ref cpu_buffer) => {
                     assert_eq!(mip_sizes.len(), 1);
@@ -3464,7 +3490,7 @@ impl hal::device::Device<Backend> for Device {
         Ok(is_ready)
     }
 
-    fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), d::WaitError> {
         command::QueueInner::wait_idle(&self.shared.queue);
         Ok(())
     }
@@ -205,6 +205,7 @@ struct Shared {
     disabilities: PrivateDisabilities,
     private_caps: PrivateCapabilities,
     visibility: VisibilityShared,
+    heap_statistics: Mutex<HeapStatistics>,
 }
 
 unsafe impl Send for Shared {}
@@ -243,10 +244,20 @@ impl Shared {
             private_caps,
             device: Mutex::new(device),
             visibility,
+            heap_statistics: Mutex::new(HeapStatistics::default()),
         }
     }
 }
 
+/// Statistics of the Metal heaps backing device memory allocations.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HeapStatistics {
+    /// Number of live heaps.
+    pub heap_count: usize,
+    /// Total size of the live heaps, in bytes.
+    pub heap_bytes: u64,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Experiments {
     pub argument_buffers: bool,
@@ -705,6 +716,8 @@ struct PrivateCapabilities {
     // if TRUE, we'll report `NON_FILL_POLYGON_MODE` feature without the points support
     expose_line_mode: bool,
     resource_heaps: bool,
+    // Heaps with explicit placement of resources at an offset.
+    placement_heaps: bool,
     argument_buffers: bool,
     shared_textures: bool,
     mutable_comparison_samplers: bool,
@@ -849,6 +862,12 @@ impl PrivateCapabilities {
             read_write_texture_tier: device.read_write_texture_support(),
             expose_line_mode: true,
             resource_heaps: Self::supports_any(&device, RESOURCE_HEAP_SUPPORT),
+            placement_heaps: Self::supports_any(&device, RESOURCE_HEAP_SUPPORT)
+                && if os_is_mac {
+                    Self::version_at_least(major, minor, 10, 15)
+                } else {
+                    Self::version_at_least(major, minor, 13, 0)
+                },
             argument_buffers: experiments.argument_buffers
                 && Self::supports_any(&device, ARGUMENT_BUFFER_SUPPORT),
             shared_textures: !os_is_mac,
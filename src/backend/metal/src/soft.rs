@@ -157,6 +157,9 @@ pub enum BlitCommand {
         dst: BufferPtr,
         region: hal::command::BufferImageCopy,
     },
+    GenerateMipmaps {
+        texture: TexturePtr,
+    },
 }
 
 #[derive(Clone, Debug)]
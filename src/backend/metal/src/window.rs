@@ -200,6 +200,7 @@ impl w::Surface<Backend> for Surface {
             composite_alpha_modes: w::CompositeAlphaMode::OPAQUE
                 | w::CompositeAlphaMode::POSTMULTIPLIED
                 | w::CompositeAlphaMode::INHERIT,
+            color_spaces: w::ColorSpace::SRGB_NONLINEAR,
             //Note: this is hardcoded in `CAMetalLayer` documentation
             image_count: if can_set_maximum_drawables_count {
                 2..=3
@@ -244,6 +245,9 @@ impl w::PresentationSurface<Backend> for Surface {
         if !image::Usage::COLOR_ATTACHMENT.contains(config.image_usage) {
             warn!("Swapchain usage {:?} is not expected", config.image_usage);
         }
+        if config.color_space != w::ColorSpace::SRGB_NONLINEAR {
+            return Err(w::SwapchainError::UnsupportedColorSpace);
+        }
         #[cfg(target_os = "macos")]
         {
             if self.view.is_some() && self.main_thread_id != thread::current().id() {
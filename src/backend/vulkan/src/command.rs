@@ -182,7 +182,10 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         flags: com::CommandBufferFlags,
         info: com::CommandBufferInheritanceInfo<Backend>,
     ) {
-        let inheritance_info =
+        let mut conditional_rendering_info =
+            vk::CommandBufferInheritanceConditionalRenderingInfoEXT::builder()
+                .conditional_rendering_enable(true);
+        let mut inheritance_info =
             vk::CommandBufferInheritanceInfo::builder()
                 .render_pass(
                     info.subpass
@@ -200,6 +203,9 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
                 .occlusion_query_enable(info.occlusion_query_enable)
                 .query_flags(conv::map_query_control_flags(info.occlusion_query_flags))
                 .pipeline_statistics(conv::map_pipeline_statistics(info.pipeline_statistics));
+        if info.conditional_rendering_enable {
+            inheritance_info = inheritance_info.push_next(&mut conditional_rendering_info);
+        }
 
         let info = vk::CommandBufferBeginInfo::builder()
             .flags(conv::map_command_buffer_flags(flags))
@@ -341,9 +347,16 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
     }
 
     unsafe fn update_buffer(&mut self, buffer: &n::Buffer, offset: buffer::Offset, data: &[u8]) {
-        self.device
-            .raw
-            .cmd_update_buffer(self.raw, buffer.raw, offset, data);
+        // `vkCmdUpdateBuffer` accepts at most 65536 bytes per call.
+        const MAX_UPDATE_SIZE: usize = 1 << 16;
+        for (i, chunk) in data.chunks(MAX_UPDATE_SIZE).enumerate() {
+            self.device.raw.cmd_update_buffer(
+                self.raw,
+                buffer.raw,
+                offset + (i * MAX_UPDATE_SIZE) as buffer::Offset,
+                chunk,
+            );
+        }
     }
 
     unsafe fn clear_image<T>(
@@ -513,6 +526,65 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         });
     }
 
+    unsafe fn generate_mipmaps(&mut self, image: &n::Image) {
+        let level_offset = |level: u32| vk::Offset3D {
+            x: (image.extent.width >> level).max(1) as i32,
+            y: (image.extent.height >> level).max(1) as i32,
+            z: (image.extent.depth >> level).max(1) as i32,
+        };
+        let layers = |level: u32| vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: level,
+            base_array_layer: 0,
+            layer_count: image.array_layers,
+        };
+
+        for level in 1..image.mip_levels {
+            // Wait for the previous level to be written before reading from it.
+            let barrier = vk::ImageMemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+                .old_layout(vk::ImageLayout::GENERAL)
+                .new_layout(vk::ImageLayout::GENERAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image.raw)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: level - 1,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: image.array_layers,
+                })
+                .build();
+            self.device.raw.cmd_pipeline_barrier(
+                self.raw,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[barrier],
+            );
+
+            let region = vk::ImageBlit {
+                src_subresource: layers(level - 1),
+                src_offsets: [vk::Offset3D::default(), level_offset(level - 1)],
+                dst_subresource: layers(level),
+                dst_offsets: [vk::Offset3D::default(), level_offset(level)],
+            };
+            self.device.raw.cmd_blit_image(
+                self.raw,
+                image.raw,
+                vk::ImageLayout::GENERAL,
+                image.raw,
+                vk::ImageLayout::GENERAL,
+                &[region],
+                vk::Filter::LINEAR,
+            );
+        }
+    }
+
     unsafe fn bind_index_buffer(
         &mut self,
         buffer: &n::Buffer,
@@ -1048,6 +1120,42 @@ impl com::CommandBuffer<Backend> for CommandBuffer {
         )
     }
 
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        inverted: bool,
+    ) {
+        let flags = if inverted {
+            vk::ConditionalRenderingFlagsEXT::INVERTED
+        } else {
+            vk::ConditionalRenderingFlagsEXT::empty()
+        };
+        let info = vk::ConditionalRenderingBeginInfoEXT::builder()
+            .buffer(buffer.raw)
+            .offset(offset)
+            .flags(flags);
+        self.device
+            .extension_fns
+            .conditional_rendering
+            .as_ref()
+            .expect(
+                "Feature CONDITIONAL_RENDERING must be enabled to call begin_conditional_rendering",
+            )
+            .cmd_begin_conditional_rendering_ext(self.raw, &*info);
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        self.device
+            .extension_fns
+            .conditional_rendering
+            .as_ref()
+            .expect(
+                "Feature CONDITIONAL_RENDERING must be enabled to call end_conditional_rendering",
+            )
+            .cmd_end_conditional_rendering_ext(self.raw);
+    }
+
     unsafe fn push_compute_constants(
         &mut self,
         layout: &n::PipelineLayout,
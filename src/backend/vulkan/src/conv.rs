@@ -6,7 +6,7 @@ use hal::{
     buffer, command, format, image, memory,
     memory::Segment,
     pass, pso, query,
-    window::{CompositeAlphaMode, PresentMode},
+    window::{ColorSpace, CompositeAlphaMode, PresentMode},
     IndexType,
 };
 
@@ -342,6 +342,28 @@ pub fn map_blend_factor(factor: pso::Factor) -> vk::BlendFactor {
     }
 }
 
+pub fn map_logic_op(op: &pso::LogicOp) -> vk::LogicOp {
+    use hal::pso::LogicOp::*;
+    match *op {
+        Clear => vk::LogicOp::CLEAR,
+        And => vk::LogicOp::AND,
+        AndReverse => vk::LogicOp::AND_REVERSE,
+        Copy => vk::LogicOp::COPY,
+        AndInverted => vk::LogicOp::AND_INVERTED,
+        NoOp => vk::LogicOp::NO_OP,
+        Xor => vk::LogicOp::XOR,
+        Or => vk::LogicOp::OR,
+        Nor => vk::LogicOp::NOR,
+        Equivalent => vk::LogicOp::EQUIVALENT,
+        Invert => vk::LogicOp::INVERT,
+        OrReverse => vk::LogicOp::OR_REVERSE,
+        CopyInverted => vk::LogicOp::COPY_INVERTED,
+        OrInverted => vk::LogicOp::OR_INVERTED,
+        Nand => vk::LogicOp::NAND,
+        Set => vk::LogicOp::SET,
+    }
+}
+
 pub fn map_blend_op(operation: pso::BlendOp) -> (vk::BlendOp, vk::BlendFactor, vk::BlendFactor) {
     use hal::pso::BlendOp::*;
     match operation {
@@ -570,6 +592,28 @@ pub fn map_vk_present_mode(mode: vk::PresentModeKHR) -> PresentMode {
     }
 }
 
+/// Returns `None` unless exactly one color space is specified.
+pub fn map_color_space(color_space: ColorSpace) -> Option<vk::ColorSpaceKHR> {
+    if color_space == ColorSpace::SRGB_NONLINEAR {
+        Some(vk::ColorSpaceKHR::SRGB_NONLINEAR)
+    } else if color_space == ColorSpace::EXTENDED_SRGB_LINEAR {
+        Some(vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT)
+    } else if color_space == ColorSpace::HDR10_ST2084 {
+        Some(vk::ColorSpaceKHR::HDR10_ST2084_EXT)
+    } else {
+        None
+    }
+}
+
+pub fn map_vk_color_space(color_space: vk::ColorSpaceKHR) -> ColorSpace {
+    match color_space {
+        vk::ColorSpaceKHR::SRGB_NONLINEAR => ColorSpace::SRGB_NONLINEAR,
+        vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => ColorSpace::EXTENDED_SRGB_LINEAR,
+        vk::ColorSpaceKHR::HDR10_ST2084_EXT => ColorSpace::HDR10_ST2084,
+        _ => ColorSpace::empty(),
+    }
+}
+
 pub fn map_composite_alpha_mode(
     composite_alpha_mode: CompositeAlphaMode,
 ) -> vk::CompositeAlphaFlagsKHR {
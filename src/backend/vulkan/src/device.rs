@@ -30,6 +30,9 @@ struct GraphicsPipelineInfoBuf<'a> {
 
     vertex_bindings: Vec<vk::VertexInputBindingDescription>,
     vertex_attributes: Vec<vk::VertexInputAttributeDescription>,
+    vertex_binding_divisors: Vec<vk::VertexInputBindingDivisorDescriptionEXT>,
+    // Boxed so the pointer chained from `vertex_input_state` survives moving `Self`.
+    vertex_input_divisor_state: Option<Box<vk::PipelineVertexInputDivisorStateCreateInfoEXT>>,
     blend_states: Vec<vk::PipelineColorBlendAttachmentState>,
 
     sample_mask: [u32; 2],
@@ -111,19 +114,42 @@ impl<'a> GraphicsPipelineInfoBuf<'a> {
                     this.add_stage(vk::ShaderStageFlags::TESSELLATION_CONTROL, &ts.0);
                     this.add_stage(vk::ShaderStageFlags::TESSELLATION_EVALUATION, &ts.1);
                 }
-                this.vertex_bindings = buffers.iter().map(|vbuf| {
-                    vk::VertexInputBindingDescription {
+                this.vertex_bindings = buffers
+                    .iter()
+                    .map(|vbuf| vk::VertexInputBindingDescription {
                         binding: vbuf.binding,
                         stride: vbuf.stride as u32,
                         input_rate: match vbuf.rate {
                             VertexInputRate::Vertex => vk::VertexInputRate::VERTEX,
-                            VertexInputRate::Instance(divisor) => {
-                                debug_assert_eq!(divisor, 1, "Custom vertex rate divisors not supported in Vulkan backend without extension");
-                                vk::VertexInputRate::INSTANCE
-                            },
+                            VertexInputRate::Instance(_) => vk::VertexInputRate::INSTANCE,
                         },
+                    })
+                    .collect();
+
+                for vbuf in buffers.iter() {
+                    match vbuf.rate {
+                        VertexInputRate::Instance(divisor) if divisor != 1 => {
+                            if device.features.contains(Features::INSTANCE_RATE) {
+                                this.vertex_binding_divisors.push(
+                                    vk::VertexInputBindingDivisorDescriptionEXT {
+                                        binding: vbuf.binding,
+                                        divisor: divisor as u32,
+                                    },
+                                );
+                            } else {
+                                warn!("Instance rate divisor {} was requested on a device with disabled feature", divisor);
+                            }
+                        }
+                        _ => {}
                     }
-                }).collect();
+                }
+                if !this.vertex_binding_divisors.is_empty() {
+                    this.vertex_input_divisor_state = Some(Box::new(
+                        vk::PipelineVertexInputDivisorStateCreateInfoEXT::builder()
+                            .vertex_binding_divisors(&this.vertex_binding_divisors)
+                            .build(),
+                    ));
+                }
 
                 this.vertex_attributes = attributes
                     .iter()
@@ -135,11 +161,16 @@ impl<'a> GraphicsPipelineInfoBuf<'a> {
                     })
                     .collect();
 
-                this.vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
-                    .flags(vk::PipelineVertexInputStateCreateFlags::empty())
-                    .vertex_binding_descriptions(&this.vertex_bindings)
-                    .vertex_attribute_descriptions(&this.vertex_attributes)
-                    .build();
+                let mut vertex_input_state_builder =
+                    vk::PipelineVertexInputStateCreateInfo::builder()
+                        .flags(vk::PipelineVertexInputStateCreateFlags::empty())
+                        .vertex_binding_descriptions(&this.vertex_bindings)
+                        .vertex_attribute_descriptions(&this.vertex_attributes);
+                if let Some(ref mut divisor_state) = this.vertex_input_divisor_state {
+                    vertex_input_state_builder =
+                        vertex_input_state_builder.push_next(&mut **divisor_state);
+                }
+                this.vertex_input_state = vertex_input_state_builder.build();
 
                 this.input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
                     .flags(vk::PipelineInputAssemblyStateCreateFlags::empty())
@@ -401,8 +432,11 @@ impl<'a> GraphicsPipelineInfoBuf<'a> {
 
         this.color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
             .flags(vk::PipelineColorBlendStateCreateFlags::empty())
-            .logic_op_enable(false) // TODO
-            .logic_op(vk::LogicOp::CLEAR)
+            .logic_op_enable(desc.blender.logic_op.is_some())
+            .logic_op(match desc.blender.logic_op {
+                Some(ref op) => conv::map_logic_op(op),
+                None => vk::LogicOp::CLEAR,
+            })
             .attachments(&this.blend_states) // TODO:
             .blend_constants(match desc.baked_states.blend_constants {
                 Some(value) => value,
@@ -1150,6 +1184,8 @@ impl d::Device<B> for super::Device {
                 ty: image_type,
                 flags,
                 extent,
+                mip_levels: mip_levels as u32,
+                array_layers: array_layers as u32,
             }),
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
@@ -1290,10 +1326,32 @@ impl d::Device<B> for super::Device {
                 // TODO raw_bindings doesnt implement fmt::Debug
                 // debug!("create_descriptor_set_layout {:?}", raw_bindings);
 
-                let info = vk::DescriptorSetLayoutCreateInfo::builder()
+                let mut info = vk::DescriptorSetLayoutCreateInfo::builder()
                     .flags(vk::DescriptorSetLayoutCreateFlags::empty())
                     .bindings(&raw_bindings);
 
+                let binding_flags;
+                let mut binding_flags_info;
+                if bindings.iter().any(|b| b.partially_bound) {
+                    debug_assert!(self
+                        .shared
+                        .features
+                        .contains(Features::PARTIALLY_BOUND_DESCRIPTOR));
+                    binding_flags = bindings
+                        .iter()
+                        .map(|b| {
+                            if b.partially_bound {
+                                vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                            } else {
+                                vk::DescriptorBindingFlags::empty()
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    binding_flags_info = vk::DescriptorSetLayoutBindingFlagsCreateInfo::builder()
+                        .binding_flags(&binding_flags);
+                    info = info.push_next(&mut binding_flags_info);
+                }
+
                 self.shared.raw.create_descriptor_set_layout(&info, None)
             })
         });
@@ -1784,11 +1842,12 @@ impl d::Device<B> for super::Device {
         self.shared.raw.destroy_event(event.0, None);
     }
 
-    fn wait_idle(&self) -> Result<(), d::OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), d::WaitError> {
         match unsafe { self.shared.raw.device_wait_idle() } {
             Ok(()) => Ok(()),
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(d::OutOfMemory::Device.into()),
+            Err(vk::Result::ERROR_DEVICE_LOST) => Err(d::DeviceLost.into()),
             _ => unreachable!(),
         }
     }
@@ -2455,6 +2514,8 @@ impl d::Device<B> for super::Device {
                 ty: image_type,
                 flags,
                 extent,
+                mip_levels: mip_levels as u32,
+                array_layers: array_layers as u32,
             },
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => return Err(d::OutOfMemory::Host.into()),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
@@ -2652,6 +2713,8 @@ impl d::Device<B> for super::Device {
                 ty: image_type,
                 flags,
                 extent,
+                mip_levels: mip_levels as u32,
+                array_layers: array_layers as u32,
             },
             Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => return Err(d::OutOfMemory::Host.into()),
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
@@ -3073,6 +3136,37 @@ impl super::Device {
         panic!("Unable to get Ash memory type for {:?}", hal_type);
     }
 
+    /// Compiles GLSL source for a single shader stage into SPIR-V at runtime
+    /// with shaderc and creates a shader module from the result.
+    ///
+    /// This is a separate entry point: `Device::create_shader_module` only
+    /// accepts SPIR-V, so GLSL must be passed here instead.
+    #[cfg(feature = "glsl")]
+    pub unsafe fn create_shader_module_from_glsl(
+        &self,
+        source: &str,
+        stage: pso::ShaderStageFlags,
+    ) -> Result<n::ShaderModule, d::ShaderError> {
+        use shaderc::ShaderKind;
+
+        let kind = match stage {
+            pso::ShaderStageFlags::VERTEX => ShaderKind::Vertex,
+            pso::ShaderStageFlags::HULL => ShaderKind::TessControl,
+            pso::ShaderStageFlags::DOMAIN => ShaderKind::TessEvaluation,
+            pso::ShaderStageFlags::GEOMETRY => ShaderKind::Geometry,
+            pso::ShaderStageFlags::FRAGMENT => ShaderKind::Fragment,
+            pso::ShaderStageFlags::COMPUTE => ShaderKind::Compute,
+            _ => return Err(d::ShaderError::Unsupported),
+        };
+        let mut compiler = shaderc::Compiler::new().ok_or_else(|| {
+            d::ShaderError::CompilationFailed("Unable to create shaderc compiler".to_string())
+        })?;
+        let artifact = compiler
+            .compile_into_spirv(source, kind, "shader.glsl", "main", None)
+            .map_err(|e| d::ShaderError::CompilationFailed(e.to_string()))?;
+        d::Device::create_shader_module(self, artifact.as_binary())
+    }
+
     pub(crate) unsafe fn create_swapchain(
         &self,
         surface: &mut w::Surface,
@@ -3086,12 +3180,37 @@ impl super::Device {
             None => vk::SwapchainKHR::null(),
         };
 
+        // Color spaces are only supported with specific formats.
+        let format = conv::map_format(config.format);
+        let color_space = conv::map_color_space(config.color_space)
+            .ok_or(hal::window::SwapchainError::UnsupportedColorSpace)?;
+        let surface_formats = match surface
+            .raw
+            .functor
+            .get_physical_device_surface_formats(self.shared.physical_device, surface.raw.handle)
+        {
+            Ok(formats) => formats,
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => return Err(d::OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                return Err(d::OutOfMemory::Device.into())
+            }
+            Err(vk::Result::ERROR_SURFACE_LOST_KHR) => return Err(hal::window::SurfaceLost.into()),
+            Err(_) => return Err(hal::window::SwapchainError::Unknown),
+        };
+        let is_supported = surface_formats.iter().any(|sf| {
+            (sf.format == format || sf.format == vk::Format::UNDEFINED)
+                && sf.color_space == color_space
+        });
+        if !is_supported {
+            return Err(hal::window::SwapchainError::UnsupportedColorSpace);
+        }
+
         let info = vk::SwapchainCreateInfoKHR::builder()
             .flags(vk::SwapchainCreateFlagsKHR::empty())
             .surface(surface.raw.handle)
             .min_image_count(config.image_count)
-            .image_format(conv::map_format(config.format))
-            .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
+            .image_format(format)
+            .image_color_space(color_space)
             .image_extent(vk::Extent2D {
                 width: config.extent.width,
                 height: config.extent.height,
@@ -3162,6 +3281,8 @@ impl super::Device {
                 ty: vk::ImageType::TYPE_2D,
                 flags: vk::ImageCreateFlags::empty(),
                 extent,
+                mip_levels: 1,
+                array_layers: 1,
             })
             .collect();
 
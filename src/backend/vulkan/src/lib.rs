@@ -48,7 +48,7 @@ use hal::{
 
 use std::{
     borrow::Cow,
-    cmp,
+    cmp, env,
     ffi::{CStr, CString},
     fmt, slice,
     sync::Arc,
@@ -69,6 +69,12 @@ pub use physical_device::*;
 // Sets up the maximum count we expect in most cases, but maybe not all of them.
 const ROUGH_MAX_ATTACHMENT_COUNT: usize = 5;
 
+/// Validation layers and debug callbacks are always enabled in debug builds,
+/// release builds can opt in with `GFX_VULKAN_VALIDATION`.
+fn validation_enabled() -> bool {
+    cfg!(debug_assertions) || env::var("GFX_VULKAN_VALIDATION").is_ok()
+}
+
 pub struct RawInstance {
     inner: ash::Instance,
     handle_is_external: bool,
@@ -378,8 +384,11 @@ impl Instance {
             extensions.push(ash::extensions::mvk::MacOSSurface::name());
         }
 
+        // Exposes the HDR color spaces of `ColorSpace`.
+        extensions.push(vk::ExtSwapchainColorspaceFn::name());
+
         extensions.push(ext::DebugUtils::name());
-        if cfg!(debug_assertions) {
+        if validation_enabled() {
             #[allow(deprecated)]
             extensions.push(ext::DebugReport::name());
         }
@@ -427,7 +436,7 @@ impl Instance {
 
         // Check requested layers against the available layers
         let mut layers: Vec<&'static CStr> = Vec::new();
-        if cfg!(debug_assertions) {
+        if validation_enabled() {
             layers.push(CStr::from_bytes_with_nul(b"VK_LAYER_KHRONOS_validation\0").unwrap());
         }
 
@@ -538,7 +547,7 @@ impl Instance {
                     .pfn_user_callback(Some(debug_utils_messenger_callback));
                 let handle = unsafe { ext.create_debug_utils_messenger(&info, None) }.unwrap();
                 Some(DebugMessenger::Utils(ext, handle))
-            } else if cfg!(debug_assertions)
+            } else if validation_enabled()
                 && instance_extensions.iter().any(|props| unsafe {
                     CStr::from_ptr(props.extension_name.as_ptr()) == ext::DebugReport::name()
                 })
@@ -830,6 +839,7 @@ struct DeviceExtensionFunctions {
     mesh_shaders: Option<ExtensionFn<MeshShader>>,
     draw_indirect_count: Option<ExtensionFn<khr::DrawIndirectCount>>,
     display_control: Option<vk::ExtDisplayControlFn>,
+    conditional_rendering: Option<vk::ExtConditionalRenderingFn>,
     memory_requirements2: Option<ExtensionFn<vk::KhrGetMemoryRequirements2Fn>>,
     // The extension does not have its own functions.
     dedicated_allocation: Option<ExtensionFn<()>>,
@@ -869,6 +879,7 @@ impl<T> ExtensionFn<T> {
 #[doc(hidden)]
 pub struct RawDevice {
     raw: ash::Device,
+    physical_device: vk::PhysicalDevice,
     handle_is_external: bool,
     features: Features,
     instance: Arc<RawInstance>,
@@ -1134,14 +1145,14 @@ impl queue::Queue<Backend> for Queue {
         let fence_raw = fence.map(|fence| fence.0).unwrap_or(vk::Fence::null());
 
         // TODO temporary hack as method is not yet exposed, https://github.com/MaikKlein/ash/issues/342
-        assert_eq!(
-            vk::Result::SUCCESS,
-            device
-                .shared
-                .raw
-                .fp_v1_0()
-                .queue_bind_sparse(*self.raw, 1, &info, fence_raw)
-        );
+        let result = device
+            .shared
+            .raw
+            .fp_v1_0()
+            .queue_bind_sparse(*self.raw, 1, &info, fence_raw);
+        if result != vk::Result::SUCCESS {
+            error!("Sparse binding resulted in {:?}", result);
+        }
     }
 
     unsafe fn present(
@@ -1156,10 +1167,19 @@ impl queue::Queue<Backend> for Queue {
         } else {
             let signals = &[ssc.semaphore.0];
             let submit_info = vk::SubmitInfo::builder().signal_semaphores(signals);
-            self.device
+            match self
+                .device
                 .raw
                 .queue_submit(*self.raw, &[*submit_info], vk::Fence::null())
-                .unwrap();
+            {
+                Ok(()) => {}
+                Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => return Err(OutOfMemory::Host.into()),
+                Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+                    return Err(OutOfMemory::Device.into())
+                }
+                Err(vk::Result::ERROR_DEVICE_LOST) => return Err(DeviceLost.into()),
+                Err(e) => panic!("Unexpected submission error {:?}", e),
+            }
             ssc.semaphore.0
         };
 
@@ -1183,11 +1203,12 @@ impl queue::Queue<Backend> for Queue {
         }
     }
 
-    fn wait_idle(&mut self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), hal::device::WaitError> {
         match unsafe { self.device.raw.queue_wait_idle(*self.raw) } {
             Ok(()) => Ok(()),
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::Host),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::Device),
+            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::Host.into()),
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::Device.into()),
+            Err(vk::Result::ERROR_DEVICE_LOST) => Err(DeviceLost.into()),
             Err(_) => unreachable!(),
         }
     }
@@ -49,6 +49,8 @@ pub struct Image {
     pub(crate) ty: vk::ImageType,
     pub(crate) flags: vk::ImageCreateFlags,
     pub(crate) extent: vk::Extent3D,
+    pub(crate) mip_levels: u32,
+    pub(crate) array_layers: u32,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
@@ -31,6 +31,8 @@ pub struct PhysicalDeviceFeatures {
     descriptor_indexing: Option<vk::PhysicalDeviceDescriptorIndexingFeaturesEXT>,
     mesh_shader: Option<vk::PhysicalDeviceMeshShaderFeaturesNV>,
     imageless_framebuffer: Option<vk::PhysicalDeviceImagelessFramebufferFeaturesKHR>,
+    vertex_attribute_divisor: Option<vk::PhysicalDeviceVertexAttributeDivisorFeaturesEXT>,
+    conditional_rendering: Option<vk::PhysicalDeviceConditionalRenderingFeaturesEXT>,
 }
 
 // This is safe because the structs have `p_next: *mut c_void`, which we null out/never read.
@@ -57,6 +59,12 @@ impl PhysicalDeviceFeatures {
         if let Some(ref mut feature) = self.imageless_framebuffer {
             info = info.push_next(feature);
         }
+        if let Some(ref mut feature) = self.vertex_attribute_divisor {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.conditional_rendering {
+            info = info.push_next(feature);
+        }
 
         info
     }
@@ -185,6 +193,9 @@ impl PhysicalDeviceFeatures {
                         .runtime_descriptor_array(
                             features.contains(Features::UNSIZED_DESCRIPTOR_ARRAY),
                         )
+                        .descriptor_binding_partially_bound(
+                            features.contains(Features::PARTIALLY_BOUND_DESCRIPTOR),
+                        )
                         .sampler_filter_minmax(features.contains(Features::SAMPLER_REDUCTION))
                         .imageless_framebuffer(supports_vulkan12_imageless_framebuffer)
                         .build(),
@@ -212,6 +223,9 @@ impl PhysicalDeviceFeatures {
                         .runtime_descriptor_array(
                             features.contains(Features::UNSIZED_DESCRIPTOR_ARRAY),
                         )
+                        .descriptor_binding_partially_bound(
+                            features.contains(Features::PARTIALLY_BOUND_DESCRIPTOR),
+                        )
                         .build(),
                 )
             } else {
@@ -238,6 +252,33 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            vertex_attribute_divisor: if enabled_extensions
+                .contains(&vk::ExtVertexAttributeDivisorFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceVertexAttributeDivisorFeaturesEXT::builder()
+                        .vertex_attribute_instance_rate_divisor(
+                            features.contains(Features::INSTANCE_RATE),
+                        )
+                        .build(),
+                )
+            } else {
+                None
+            },
+            conditional_rendering: if enabled_extensions
+                .contains(&vk::ExtConditionalRenderingFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceConditionalRenderingFeaturesEXT::builder()
+                        .conditional_rendering(features.contains(Features::CONDITIONAL_RENDERING))
+                        .inherited_conditional_rendering(
+                            features.contains(Features::CONDITIONAL_RENDERING),
+                        )
+                        .build(),
+                )
+            } else {
+                None
+            },
         }
     }
 
@@ -465,6 +506,9 @@ impl PhysicalDeviceFeatures {
             if vulkan_1_2.runtime_descriptor_array != 0 {
                 bits |= Features::UNSIZED_DESCRIPTOR_ARRAY;
             }
+            if vulkan_1_2.descriptor_binding_partially_bound != 0 {
+                bits |= Features::PARTIALLY_BOUND_DESCRIPTOR;
+            }
             if vulkan_1_2.sampler_mirror_clamp_to_edge != 0 {
                 bits |= Features::SAMPLER_MIRROR_CLAMP_EDGE;
             }
@@ -492,6 +536,9 @@ impl PhysicalDeviceFeatures {
             if descriptor_indexing.runtime_descriptor_array != 0 {
                 bits |= Features::UNSIZED_DESCRIPTOR_ARRAY;
             }
+            if descriptor_indexing.descriptor_binding_partially_bound != 0 {
+                bits |= Features::PARTIALLY_BOUND_DESCRIPTOR;
+            }
         }
 
         if let Some(ref mesh_shader) = self.mesh_shader {
@@ -503,6 +550,22 @@ impl PhysicalDeviceFeatures {
             }
         }
 
+        if let Some(ref vertex_attribute_divisor) = self.vertex_attribute_divisor {
+            if vertex_attribute_divisor.vertex_attribute_instance_rate_divisor != 0 {
+                bits |= Features::INSTANCE_RATE;
+            }
+        }
+
+        if let Some(ref conditional_rendering) = self.conditional_rendering {
+            // Both are needed, since secondary command buffers may be executed
+            // while conditional rendering is active.
+            if conditional_rendering.conditional_rendering != 0
+                && conditional_rendering.inherited_conditional_rendering != 0
+            {
+                bits |= Features::CONDITIONAL_RENDERING;
+            }
+        }
+
         bits
     }
 }
@@ -581,6 +644,14 @@ impl PhysicalDeviceInfo {
             requested_extensions.push(MeshShader::name());
         }
 
+        if requested_features.contains(Features::INSTANCE_RATE) {
+            requested_extensions.push(vk::ExtVertexAttributeDivisorFn::name());
+        }
+
+        if requested_features.contains(Features::CONDITIONAL_RENDERING) {
+            requested_extensions.push(vk::ExtConditionalRenderingFn::name());
+        }
+
         if self.api_version() < Version::V1_2
             && requested_features.contains(Features::DRAW_INDIRECT_COUNT)
         {
@@ -596,6 +667,11 @@ impl PhysicalDeviceInfo {
             requested_extensions.push(vk::ExtDisplayControlFn::name());
         }
 
+        // Required by `memory_budget`, which is only available if the device enabled it.
+        if self.supports_extension(vk::ExtMemoryBudgetFn::name()) {
+            requested_extensions.push(vk::ExtMemoryBudgetFn::name());
+        }
+
         if requested_features.contains(Features::EXTERNAL_MEMORY) {
             if self.api_version() < Version::V1_1 {
                 requested_extensions.push(vk::KhrGetPhysicalDeviceProperties2Fn::name());
@@ -679,6 +755,22 @@ impl PhysicalDeviceInfo {
                 mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
             }
 
+            if device_properties.supports_extension(vk::ExtVertexAttributeDivisorFn::name()) {
+                features.vertex_attribute_divisor =
+                    Some(vk::PhysicalDeviceVertexAttributeDivisorFeaturesEXT::builder().build());
+
+                let mut_ref = features.vertex_attribute_divisor.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
+            if device_properties.supports_extension(vk::ExtConditionalRenderingFn::name()) {
+                features.conditional_rendering =
+                    Some(vk::PhysicalDeviceConditionalRenderingFeaturesEXT::builder().build());
+
+                let mut_ref = features.conditional_rendering.as_mut().unwrap();
+                mut_ref.p_next = mem::replace(&mut features2.p_next, mut_ref as *mut _ as *mut _);
+            }
+
             match get_device_properties {
                 ExtensionFn::Promoted => {
                     use ash::version::InstanceV1_1;
@@ -713,6 +805,8 @@ impl PhysicalDeviceInfo {
             null_p_next(&mut features.descriptor_indexing);
             null_p_next(&mut features.mesh_shader);
             null_p_next(&mut features.imageless_framebuffer);
+            null_p_next(&mut features.vertex_attribute_divisor);
+            null_p_next(&mut features.conditional_rendering);
         }
 
         (device_properties, features)
@@ -812,6 +906,19 @@ impl PhysicalDevice {
             None
         };
 
+        let conditional_rendering =
+            if enabled_extensions.contains(&vk::ExtConditionalRenderingFn::name()) {
+                Some(vk::ExtConditionalRenderingFn::load(|name| {
+                    std::mem::transmute(
+                        self.instance
+                            .inner
+                            .get_device_proc_addr(device_raw.handle(), name.as_ptr()),
+                    )
+                }))
+            } else {
+                None
+            };
+
         let memory_requirements2 =
             if enabled_extensions.contains(&vk::KhrGetMemoryRequirements2Fn::name()) {
                 Some(ExtensionFn::Extension(
@@ -975,6 +1082,7 @@ impl PhysicalDevice {
         let device = Device {
             shared: Arc::new(RawDevice {
                 raw: device_raw,
+                physical_device: self.handle,
                 handle_is_external,
                 features: requested_features,
                 instance: Arc::clone(&self.instance),
@@ -982,6 +1090,7 @@ impl PhysicalDevice {
                     mesh_shaders: mesh_fn,
                     draw_indirect_count: indirect_count_fn,
                     display_control,
+                    conditional_rendering,
                     memory_requirements2: memory_requirements2,
                     dedicated_allocation: dedicated_allocation,
                     external_memory,
@@ -1820,6 +1929,49 @@ impl adapter::PhysicalDevice<Backend> for PhysicalDevice {
         }
     }
 
+    fn memory_budget(&self) -> Option<Vec<adapter::MemoryHeapBudget>> {
+        if !self
+            .device_info
+            .supports_extension(vk::ExtMemoryBudgetFn::name())
+        {
+            return None;
+        }
+        let get_physical_device_properties =
+            self.instance.get_physical_device_properties.as_ref()?;
+
+        let mut budget_properties = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::builder();
+        let mut memory_properties2 = vk::PhysicalDeviceMemoryProperties2::builder()
+            .push_next(&mut budget_properties)
+            .build();
+
+        match get_physical_device_properties {
+            ExtensionFn::Promoted => {
+                use ash::version::InstanceV1_1;
+                unsafe {
+                    self.instance.inner.get_physical_device_memory_properties2(
+                        self.handle,
+                        &mut memory_properties2,
+                    );
+                }
+            }
+            ExtensionFn::Extension(get_physical_device_properties) => unsafe {
+                get_physical_device_properties.get_physical_device_memory_properties2_khr(
+                    self.handle,
+                    &mut memory_properties2,
+                );
+            },
+        }
+
+        let heap_count = memory_properties2.memory_properties.memory_heap_count as usize;
+        Some(
+            budget_properties.heap_budget[..heap_count]
+                .iter()
+                .zip(budget_properties.heap_usage[..heap_count].iter())
+                .map(|(&budget, &usage)| adapter::MemoryHeapBudget { budget, usage })
+                .collect(),
+        )
+    }
+
     fn is_valid_cache(&self, cache: &[u8]) -> bool {
         const HEADER_SIZE: usize = 16 + vk::UUID_SIZE;
 
@@ -312,7 +312,19 @@ impl w::Surface<Backend> for Surface {
             }
         };
 
+        let raw_formats = unsafe {
+            self.raw
+                .functor
+                .get_physical_device_surface_formats(physical_device.handle, self.raw.handle)
+                .unwrap_or_default()
+        };
+
         w::SurfaceCapabilities {
+            color_spaces: raw_formats
+                .into_iter()
+                .fold(w::ColorSpace::SRGB_NONLINEAR, |u, f| {
+                    u | conv::map_vk_color_space(f.color_space)
+                }),
             present_modes: raw_present_modes
                 .into_iter()
                 .fold(w::PresentMode::empty(), |u, m| {
@@ -462,6 +474,8 @@ impl w::PresentationSurface<Backend> for Surface {
                         ty: vk::ImageType::TYPE_2D,
                         flags: vk::ImageCreateFlags::empty(),
                         extent: ssc.swapchain.extent,
+                        mip_levels: 1,
+                        array_layers: 1,
                     },
                     view: native::ImageView {
                         image: frame.image,
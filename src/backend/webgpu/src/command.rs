@@ -7,7 +7,7 @@ use hal::{
         CommandBufferInheritanceInfo, DescriptorSetOffset, ImageBlit, ImageCopy, ImageResolve,
         Level, SubpassContents,
     },
-    device::OutOfMemory,
+    device::WaitError,
     image::{Filter, Layout, SubresourceRange},
     memory::{Barrier, Dependencies},
     pso, query,
@@ -44,7 +44,7 @@ impl hal::queue::Queue<Backend> for Queue {
         todo!()
     }
 
-    fn wait_idle(&mut self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&mut self) -> Result<(), WaitError> {
         todo!()
     }
 }
@@ -172,6 +172,10 @@ impl hal::command::CommandBuffer<Backend> for CommandBuffer {
         todo!()
     }
 
+    unsafe fn generate_mipmaps(&mut self, _image: &<Backend as hal::Backend>::Image) {
+        todo!()
+    }
+
     unsafe fn bind_index_buffer(
         &mut self,
         _buffer: &<Backend as hal::Backend>::Buffer,
@@ -511,6 +515,19 @@ impl hal::command::CommandBuffer<Backend> for CommandBuffer {
         todo!()
     }
 
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        _buffer: &<Backend as hal::Backend>::Buffer,
+        _offset: buffer::Offset,
+        _inverted: bool,
+    ) {
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
+    unsafe fn end_conditional_rendering(&mut self) {
+        debug_assert!(false, "CONDITIONAL_RENDERING is not supported");
+    }
+
     unsafe fn push_graphics_constants(
         &mut self,
         _layout: &<Backend as hal::Backend>::PipelineLayout,
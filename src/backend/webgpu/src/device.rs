@@ -445,7 +445,7 @@ impl hal::device::Device<Backend> for Device {
         todo!()
     }
 
-    fn wait_idle(&self) -> Result<(), OutOfMemory> {
+    fn wait_idle(&self) -> Result<(), WaitError> {
         todo!()
     }
 
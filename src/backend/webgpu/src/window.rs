@@ -1,8 +1,8 @@
 use hal::{
     format::Format,
     window::{
-        AcquireError, CreationError, Suboptimal, SurfaceCapabilities, SwapImageIndex,
-        SwapchainConfig,
+        AcquireError, ColorSpace, Suboptimal, SurfaceCapabilities, SwapImageIndex, SwapchainConfig,
+        SwapchainError,
     },
 };
 
@@ -30,8 +30,11 @@ impl hal::window::PresentationSurface<Backend> for Surface {
     unsafe fn configure_swapchain(
         &mut self,
         _device: &crate::Device,
-        _config: SwapchainConfig,
-    ) -> Result<(), CreationError> {
+        config: SwapchainConfig,
+    ) -> Result<(), SwapchainError> {
+        if config.color_space != ColorSpace::SRGB_NONLINEAR {
+            return Err(SwapchainError::UnsupportedColorSpace);
+        }
         todo!()
     }
 
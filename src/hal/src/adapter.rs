@@ -48,6 +48,17 @@ pub struct MemoryProperties {
     pub memory_heaps: Vec<MemoryHeap>,
 }
 
+/// Current memory budget of a heap, as reported by the driver.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryHeapBudget {
+    /// Amount of memory in bytes the process can use from the heap
+    /// before the driver starts paging memory out.
+    pub budget: u64,
+    /// Amount of memory in bytes the process currently uses from the heap.
+    pub usage: u64,
+}
+
 /// Represents a combination of a [logical device][crate::device::Device] and the
 /// [hardware queues][QueueGroup] it provides.
 ///
@@ -165,6 +176,17 @@ pub trait PhysicalDevice<B: Backend>: fmt::Debug + Any + Send + Sync {
         false
     }
 
+    /// Query the current budget of each heap in [`MemoryProperties::memory_heaps`].
+    ///
+    /// The budget changes as other processes allocate memory, so this should be called
+    /// periodically. The DX11 and DX12 backends can also signal an event when the budget
+    /// changes, see their `register_memory_budget_event`. Once the usage of a heap exceeds
+    /// its budget, allocations should be released to avoid paging. Returns `None` if the
+    /// backend can't query the budget.
+    fn memory_budget(&self) -> Option<Vec<MemoryHeapBudget>> {
+        None
+    }
+
     /// Enumerate active displays [surface][display::Display] from display.
     /// Please notice that, even if a system has displays attached, they could be not returned because they are managed by some other components.
     /// This function only return the display that are available to be managed by the current application.
@@ -85,6 +85,8 @@ bitflags!(
         const VERTEX = 0x80;
         ///
         const INDIRECT = 0x100;
+        /// Predicate for [conditional rendering](../command/trait.CommandBuffer.html#tymethod.begin_conditional_rendering).
+        const CONDITIONAL_RENDERING = 0x200;
     }
 );
 
@@ -144,5 +146,8 @@ bitflags!(
         const MEMORY_READ = 0x8000;
         ///
         const MEMORY_WRITE = 0x10000;
+        /// Read the predicate of conditional rendering in the
+        /// [`CONDITIONAL_RENDERING`](../pso/struct.PipelineStage.html#associatedconstant.CONDITIONAL_RENDERING) stage.
+        const CONDITIONAL_RENDERING_READ = 0x10_0000;
     }
 );
@@ -57,6 +57,10 @@ pub enum Level {
     /// command buffers.
     Primary,
     /// Cannot be submitted directly, but can be called from primary command buffers.
+    ///
+    /// Viewports and scissor rects can't be set in secondary command buffers on every
+    /// backend: DX12 ignores them there and uses the ones of the executing primary
+    /// command buffer instead. Portable code should set them in the primary command buffer.
     Secondary,
 }
 
@@ -89,6 +93,9 @@ pub struct CommandBufferInheritanceInfo<'a, B: Backend> {
     pub occlusion_query_enable: bool,
     pub occlusion_query_flags: query::ControlFlags,
     pub pipeline_statistics: query::PipelineStatistic,
+    /// The command buffer will be executed while conditional rendering is active
+    /// in the primary command buffer. Requires `Features::CONDITIONAL_RENDERING`.
+    pub conditional_rendering_enable: bool,
 }
 
 impl<'a, B: Backend> Default for CommandBufferInheritanceInfo<'a, B> {
@@ -99,6 +106,7 @@ impl<'a, B: Backend> Default for CommandBufferInheritanceInfo<'a, B> {
             occlusion_query_enable: false,
             occlusion_query_flags: query::ControlFlags::empty(),
             pipeline_statistics: query::PipelineStatistic::empty(),
+            conditional_rendering_enable: false,
         }
     }
 }
@@ -186,6 +194,15 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
     ) where
         T: Iterator<Item = ImageBlit>;
 
+    /// Fills every mip level of the image after the first one by downsampling
+    /// the previous level, for all array layers.
+    ///
+    /// The image must have been created with `SAMPLED`, `COLOR_ATTACHMENT`,
+    /// `TRANSFER_SRC` and `TRANSFER_DST` usage, and has to be in `Layout::General`.
+    /// The command behaves like a transfer operation and needs to be synchronized
+    /// with `PipelineStage::TRANSFER`.
+    unsafe fn generate_mipmaps(&mut self, image: &B::Image);
+
     /// Bind the index buffer view, making it the "current" one that draw commands
     /// will operate on.
     unsafe fn bind_index_buffer(
@@ -574,6 +591,36 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Requests a timestamp to be written.
     unsafe fn write_timestamp(&mut self, stage: pso::PipelineStage, query: query::Query<B>);
 
+    /// Start conditional rendering. Draws, dispatches and clears recorded until
+    /// `end_conditional_rendering` are discarded if the predicate at `offset` in `buffer`
+    /// is zero, or non-zero if `inverted` is set.
+    ///
+    /// Vulkan reads the predicate as a 32-bit value and requires `offset` to be a
+    /// multiple of 4, while DX12 reads a 64-bit value and requires a multiple of 8.
+    /// Portable code should store a 64-bit unsigned integer whose upper 32 bits are
+    /// zero at an `offset` that is a multiple of 8. Occlusion query results can be
+    /// copied into the buffer with `copy_query_pool_results` and `ResultFlags::BITS_64`
+    /// to cull draws without reading the results back on the CPU.
+    ///
+    /// There is no variant predicating directly on a query, like D3D11 predicates or
+    /// `glBeginConditionalRender`: neither Vulkan nor DX12 can predicate on a query
+    /// without copying its result into a buffer first, which the buffer form already
+    /// covers.
+    ///
+    /// Requires `Features::CONDITIONAL_RENDERING`, backends without it ignore the call.
+    /// The buffer must have been created with `Usage::CONDITIONAL_RENDERING`. Secondary
+    /// command buffers executed inside the conditional rendering block must have been
+    /// begun with `CommandBufferInheritanceInfo::conditional_rendering_enable`.
+    unsafe fn begin_conditional_rendering(
+        &mut self,
+        buffer: &B::Buffer,
+        offset: buffer::Offset,
+        inverted: bool,
+    );
+
+    /// End conditional rendering.
+    unsafe fn end_conditional_rendering(&mut self);
+
     /// Modify constant data in a graphics pipeline. Push constants are intended to modify data in a
     /// pipeline more quickly than a updating the values inside a descriptor set.
     ///
@@ -600,6 +647,10 @@ pub trait CommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
     );
 
     /// Execute the given secondary command buffers.
+    ///
+    /// The viewports and scissor rects currently set in this command buffer
+    /// are inherited by the secondary command buffers on backends that can't
+    /// record them there (see [`Level::Secondary`]).
     unsafe fn execute_commands<'a, T>(&mut self, cmd_buffers: T)
     where
         T: Iterator<Item = &'a B::CommandBuffer>;
@@ -26,6 +26,11 @@ use crate::{
 use std::{any::Any, fmt, iter, ops::Range};
 
 /// Error occurred caused device to be lost.
+///
+/// Queue submission does not report this error directly; it surfaces from the next
+/// fence wait, idle wait or presentation instead. Once observed, every object created from the
+/// device is unusable: drop them, open a new device from the adapter (enumerating
+/// adapters again if the original one was removed), and re-create all resources.
 #[derive(Clone, Debug, PartialEq, thiserror::Error)]
 #[error("Device lost")]
 pub struct DeviceLost;
@@ -676,7 +681,9 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     /// Wait for all queues associated with this device to idle.
     ///
     /// Host access to all queues needs to be **externally** sycnhronized!
-    fn wait_idle(&self) -> Result<(), OutOfMemory>;
+    ///
+    /// Returns `DeviceLost` if the device was lost while waiting.
+    fn wait_idle(&self) -> Result<(), WaitError>;
 
     /// Associate a name with an image, for easier debugging in external tools or with validation
     /// layers that can print a friendly name when referring to objects in error messages
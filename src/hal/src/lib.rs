@@ -243,7 +243,7 @@ bitflags! {
         /// Allow descriptor arrays to be unsized in shaders
         const UNSIZED_DESCRIPTOR_ARRAY = 0x0800_0000_0000_0000;
         /// Mask for all the features associated with descriptor indexing.
-        const DESCRIPTOR_INDEXING_MASK = Features::SAMPLED_TEXTURE_DESCRIPTOR_INDEXING.bits | Features::STORAGE_TEXTURE_DESCRIPTOR_INDEXING.bits | Features::UNSIZED_DESCRIPTOR_ARRAY.bits | Features::UNIFORM_BUFFER_DESCRIPTOR_INDEXING.bits | Features::STORAGE_BUFFER_DESCRIPTOR_INDEXING.bits;
+        const DESCRIPTOR_INDEXING_MASK = Features::SAMPLED_TEXTURE_DESCRIPTOR_INDEXING.bits | Features::STORAGE_TEXTURE_DESCRIPTOR_INDEXING.bits | Features::UNSIZED_DESCRIPTOR_ARRAY.bits | Features::UNIFORM_BUFFER_DESCRIPTOR_INDEXING.bits | Features::STORAGE_BUFFER_DESCRIPTOR_INDEXING.bits | Features::PARTIALLY_BOUND_DESCRIPTOR.bits;
 
         /// Enable draw_indirect_count and draw_indexed_indirect_count
         const DRAW_INDIRECT_COUNT = 0x1000_0000_0000_0000;
@@ -293,6 +293,13 @@ bitflags! {
         const SAMPLER_REDUCTION = 0x0004 << 96;
         /// Supports external memory import and export.
         const EXTERNAL_MEMORY = 0x0008 << 96;
+        /// Descriptors of bindings created with `partially_bound` can be left unwritten
+        /// if they aren't dynamically used by the shaders.
+        ///
+        /// Only exposed by the Vulkan backend.
+        const PARTIALLY_BOUND_DESCRIPTOR = 0x0010 << 96;
+        /// Supports predicating commands on a value in a buffer.
+        const CONDITIONAL_RENDERING = 0x0020 << 96;
     }
 }
 
@@ -124,6 +124,11 @@ pub struct DescriptorSetLayoutBinding {
     pub stage_flags: ShaderStageFlags,
     /// Use the associated list of immutable samplers.
     pub immutable_samplers: bool,
+    /// Descriptors of this binding that aren't dynamically used by the shaders
+    /// don't need to be written.
+    ///
+    /// Requires `Features::PARTIALLY_BOUND_DESCRIPTOR`.
+    pub partially_bound: bool,
 }
 
 /// Set of descriptors of a specific type.
@@ -79,6 +79,8 @@ bitflags!(
         /// Read/Write access from host.
         /// (Not a real pipeline stage)
         const HOST = 0x4000;
+        /// Conditional rendering predicate read.
+        const CONDITIONAL_RENDERING = 0x40000;
         /// Task shader stage.
         const TASK_SHADER = 0x80000;
         /// Mesh shader stage.
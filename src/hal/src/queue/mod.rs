@@ -9,7 +9,7 @@
 pub mod family;
 
 use crate::{
-    device::OutOfMemory,
+    device::WaitError,
     pso,
     window::{PresentError, PresentationSurface, Suboptimal},
     Backend,
@@ -143,7 +143,9 @@ pub trait Queue<B: Backend>: fmt::Debug + Any + Send + Sync {
     ) -> Result<Option<Suboptimal>, PresentError>;
 
     /// Wait for the queue to be idle.
-    fn wait_idle(&mut self) -> Result<(), OutOfMemory>;
+    ///
+    /// Returns `DeviceLost` if the device was lost while waiting.
+    fn wait_idle(&mut self) -> Result<(), WaitError>;
 
     /// The amount of nanoseconds that causes a timestamp query value to increment by one.
     fn timestamp_period(&self) -> f32;
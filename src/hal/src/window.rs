@@ -92,6 +92,9 @@ pub enum SwapchainError {
     /// Accecssing the underlying NSView from wrong thread https://github.com/gfx-rs/gfx/issues/3704
     #[error("Accecssing NSView from wrong thread")]
     WrongThread,
+    /// The surface doesn't support the requested color space with the requested format.
+    #[error("Color space is not supported with the requested format")]
+    UnsupportedColorSpace,
     /// Unknown error.
     #[error("Swapchain can't be created for an unknown reason")]
     Unknown,
@@ -199,6 +202,12 @@ pub struct SurfaceCapabilities {
 
     /// A bitmask of supported alpha composition modes.
     pub composite_alpha_modes: CompositeAlphaMode,
+
+    /// A bitmask of the color spaces supported with at least one format.
+    ///
+    /// Not every color space is available with every format. Configuring a
+    /// swapchain with an unsupported pair fails with `UnsupportedColorSpace`.
+    pub color_spaces: ColorSpace,
 }
 
 impl SurfaceCapabilities {
@@ -243,6 +252,32 @@ pub trait Surface<B: Backend>: fmt::Debug + Any + Send + Sync {
     fn supported_formats(&self, physical_device: &B::PhysicalDevice) -> Option<Vec<Format>>;
 }
 
+/// Pick the surface format that best matches `preferred`, given the list
+/// returned by [`Surface::supported_formats`].
+///
+/// The preferred format is returned if the surface supports it (or has no preference).
+/// Otherwise a format of the same bit width and channel type is chosen, which covers
+/// swizzled variants such as `Rgba8Srgb` and `Bgra8Srgb`, then any format with the same
+/// channel type, and finally the first supported format.
+pub fn select_format(supported: Option<&[Format]>, preferred: Format) -> Format {
+    let formats = match supported {
+        Some(formats) if !formats.is_empty() => formats,
+        _ => return preferred,
+    };
+    if formats.contains(&preferred) {
+        return preferred;
+    }
+
+    let channel = preferred.base_format().1;
+    let bits = preferred.surface_desc().bits;
+    formats
+        .iter()
+        .find(|f| f.base_format().1 == channel && f.surface_desc().bits == bits)
+        .or_else(|| formats.iter().find(|f| f.base_format().1 == channel))
+        .cloned()
+        .unwrap_or(formats[0])
+}
+
 /// A surface trait that exposes the ability to present images on the
 /// associtated swap chain.
 pub trait PresentationSurface<B: Backend>: Surface<B> {
@@ -297,6 +332,20 @@ bitflags!(
     }
 );
 
+bitflags!(
+    /// Specifies the color space the presentation engine interprets swapchain images in.
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct ColorSpace: u32 {
+        /// sRGB primaries with the sRGB transfer function. Always supported.
+        const SRGB_NONLINEAR = 0x1;
+        /// Linear extended-range sRGB (scRGB), typically used with `Rgba16Sfloat`.
+        const EXTENDED_SRGB_LINEAR = 0x2;
+        /// BT.2020 primaries with the SMPTE ST 2084 (PQ) transfer function,
+        /// typically used with `A2b10g10r10Unorm` for HDR10 output.
+        const HDR10_ST2084 = 0x4;
+    }
+);
+
 bitflags!(
     /// Specifies how the alpha channel of the images should be handled during
     /// compositing.
@@ -351,6 +400,9 @@ pub struct SwapchainConfig {
     pub composite_alpha_mode: CompositeAlphaMode,
     /// Format of the backbuffer images.
     pub format: Format,
+    /// Color space of the backbuffer images. Must be one of
+    /// `SurfaceCapabilities::color_spaces`.
+    pub color_space: ColorSpace,
     /// Requested image extent. Must be in
     /// `SurfaceCapabilities::extents` range.
     pub extent: Extent2D,
@@ -371,6 +423,7 @@ impl SwapchainConfig {
             present_mode: PresentMode::FIFO,
             composite_alpha_mode: CompositeAlphaMode::OPAQUE,
             format,
+            color_space: ColorSpace::SRGB_NONLINEAR,
             extent: Extent2D { width, height },
             image_count,
             image_layers: 1,
@@ -418,6 +471,7 @@ impl SwapchainConfig {
             present_mode,
             composite_alpha_mode,
             format,
+            color_space: ColorSpace::SRGB_NONLINEAR,
             extent: caps.clamped_extent(default_extent),
             image_count: DEFAULT_IMAGE_COUNT
                 .max(*caps.image_count.start())
@@ -439,6 +493,12 @@ impl SwapchainConfig {
         self
     }
 
+    /// Specify the color space of backbuffer images.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
     /// Specify the usage of backbuffer images.
     pub fn with_image_usage(mut self, usage: image::Usage) -> Self {
         self.image_usage = usage;